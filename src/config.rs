@@ -0,0 +1,732 @@
+use crate::Error;
+
+use mdbook::renderer::RenderContext;
+use serde::{Deserialize, Serialize};
+
+use std::{collections::BTreeMap, convert::TryFrom, env, fmt, path::PathBuf};
+
+/// Prefix of the environment variables overriding options of the configuration.
+const ENV_PREFIX: &str = "MDBOOK_MAN_";
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+/// Configuration of this renderer read from the `output.man` table of `book.toml`.
+pub struct ManOutputConfiguration {
+    /// If specified the pages will be saved as files rather than printed to stdout.
+    pub output_dir: Option<PathBuf>,
+    #[serde(default)]
+    /// Wether to split the book into separate files per chapter or render one man page with all chapters.
+    pub split_chapters: bool,
+    /// Override the name of the output file if `output_dir` is also specified.
+    pub filename: Option<String>,
+    /// Skip rendering of code blocks altogether.
+    pub omit_code_blocks: bool,
+    /// Text rendered in place of every code block when `omit_code_blocks` is enabled.
+    pub code_block_placeholder: Option<String>,
+    /// How code blocks are rendered.
+    pub code_block_style: CodeBlockStyle,
+    /// How inline code is rendered, in the body as well as in the titles of sections.
+    pub inline_code_style: InlineCodeStyle,
+    /// How the language of fenced code blocks is rendered as their title.
+    pub code_title_style: CodeTitleStyle,
+    /// Text put in front of the language in the title of code blocks, like `Example: `.
+    pub code_title_prefix: Option<String>,
+    /// Wrap lines of code blocks longer than this many columns. Wrapped lines end with an arrow.
+    pub code_wrap_width: Option<usize>,
+    /// Separator inserted between chapters when they are rendered as a single man page.
+    pub chapter_separator: ChapterSeparator,
+    /// How chapters with the same section title are told apart in a single man page.
+    pub duplicate_sections: DuplicateSections,
+    /// Only render the chapters selected by their index or name when splitting chapters.
+    pub chapters: Option<Vec<ChapterSelector>>,
+    /// Name of the chapter rendered as the `SYNOPSIS` section, compared case-insensitively.
+    /// Defaults to `Synopsis`.
+    pub synopsis_chapter: Option<String>,
+    /// Line length of the body text in columns.
+    pub line_length: Option<u32>,
+    /// Disable justification of the body text so that it is only aligned to the left margin.
+    pub ragged_right: bool,
+    /// Disable automatic hyphenation of words.
+    pub no_hyphenation: bool,
+    /// Order in which the chapters are rendered.
+    pub chapter_order: ChapterOrder,
+    /// Prefix the title of every section with `section_prefix` or the title of the book.
+    pub prefix_sections: bool,
+    /// Prefix used when `prefix_sections` is enabled instead of the title of the book.
+    pub section_prefix: Option<String>,
+    /// Marker rendered before checked task list items, defaults to `[x]`.
+    pub task_checked: Option<String>,
+    /// Marker rendered before unchecked task list items, defaults to `[ ]`.
+    pub task_unchecked: Option<String>,
+    /// Name of the page in the `.TH` header of the combined man page, defaults to the title of
+    /// the book. Pages of split chapters are named after the chapter unless their front matter
+    /// sets a `page-name`.
+    pub page_name: Option<String>,
+    /// Name of an index page written along with the pages of split chapters. The index
+    /// lists every page in its `SEE ALSO` section, while the pages refer back to the index.
+    pub index_page: Option<String>,
+    /// Section of the manual the index page belongs to, defaults to `section`.
+    pub index_section: Option<u8>,
+    /// Title of the manual displayed in the header of every page. Defaults to the conventional
+    /// title of the `section` for sections 1 to 8.
+    pub manual: Option<String>,
+    /// Section of the manual the pages belong to, defaults to 7.
+    pub section: Option<u8>,
+    /// Date of the last change of the pages displayed in the header of every page.
+    pub date: Option<String>,
+    /// Source of the pages displayed in the header of every page, like the name and version of
+    /// the documented project.
+    pub source: Option<String>,
+    /// Name of the table of `profiles` whose options take precedence over the other options.
+    pub profile: Option<String>,
+    /// Render the line breaks within paragraphs as they are in the source instead of filling the
+    /// lines.
+    pub hard_breaks: bool,
+    /// Don't end the pages printed to stdout with a newline.
+    pub no_trailing_newline: bool,
+    /// Render paragraphs made of a short line followed by an indented line or a line starting
+    /// with `: ` as a term and its definition.
+    pub detect_definitions: bool,
+    /// Deepest nesting of markdown elements that is rendered, anything nested deeper is skipped.
+    /// Defaults to 128.
+    pub max_depth: Option<usize>,
+    /// Macro package the pages are written in.
+    pub macro_package: MacroPackage,
+    /// Check every rendered page for malformed roff and fail instead of writing it.
+    pub check: bool,
+    /// Start every page with a comment recording the versions of mdbook-man and mdBook that
+    /// generated it.
+    pub generation_comment: bool,
+    /// Start the pages of split chapters that consist of nothing but code blocks with a `NAME`
+    /// section naming the chapter.
+    pub name_code_chapters: bool,
+    /// What is rendered in place of markdown elements this renderer doesn't support.
+    pub unhandled_nodes: UnhandledNodes,
+    /// Fail on the first markdown element this renderer doesn't support instead of rendering
+    /// the `unhandled_nodes` fallback.
+    pub strict: bool,
+    /// How tables are rendered.
+    pub table_style: TableStyle,
+    /// Embed images that are local files with `.PSPIC` for output processed with `groff -Tps`.
+    /// Other images are still rendered as their alternative text.
+    pub pspic_images: bool,
+    /// Promote the first `console`, `sh` or `text` code block of the first chapter to the
+    /// `SYNOPSIS` section if only the introduction comes before it and the book has no synopsis
+    /// chapter.
+    pub synopsis_from_usage: bool,
+    /// Continue the numbering of an ordered list starting at 1 from the previous ordered list if
+    /// only paragraphs or code blocks separate the two.
+    pub continue_ordered_lists: bool,
+    /// Render every paragraph without any visible content instead of collapsing consecutive
+    /// ones into a single break.
+    pub keep_empty_paragraphs: bool,
+    /// Name the files of split chapters after the chapter like `01-introduction.man`, prefixed
+    /// with its number zero padded to the same width for all chapters.
+    pub number_filenames: bool,
+    /// Keep whitespace at the edges of bold and italic text inside the styled run instead of
+    /// moving it next to the run.
+    pub keep_styled_whitespace: bool,
+    /// Keep in-body headings with the text following them and short examples on one page when
+    /// printing with `groff`.
+    pub keep_together: bool,
+    /// Take a level 1 heading starting a chapter as the title of its section instead of
+    /// rendering it in the body below the chapter name.
+    pub heading_as_title: bool,
+    /// Enable or disable the GitHub Flavored Markdown extensions, tables, strikethrough, task
+    /// lists, autolinks and the tag filter, at once. The options of the single extensions take
+    /// precedence. If unset all but autolinks are enabled.
+    pub gfm: Option<bool>,
+    /// Parse GFM tables, enabled by default.
+    pub tables: Option<bool>,
+    /// Parse `~~strikethrough~~`, enabled by default.
+    pub strikethrough: Option<bool>,
+    /// Parse `[x]` task list items, enabled by default.
+    pub tasklist: Option<bool>,
+    /// Parse footnote references and definitions, enabled by default.
+    pub footnotes: Option<bool>,
+    /// Where the definitions of footnotes are rendered.
+    pub footnote_style: FootnoteStyle,
+    /// Turn bare URLs and email addresses into links.
+    pub autolink: Option<bool>,
+    /// Parse description lists of terms followed by `: details`.
+    pub description_lists: Option<bool>,
+    /// Parse `^superscript^`.
+    pub superscript: Option<bool>,
+    /// Keep raw HTML in the page, comments as roff comments and other markup as written, instead
+    /// of handling it like any other element without a roff representation.
+    pub unsafe_html: bool,
+    /// How struck through text is rendered.
+    pub strikethrough_style: StrikethroughStyle,
+    /// How keys marked up with `<kbd>` are rendered.
+    pub kbd_style: KbdStyle,
+    /// What is written for a book without any chapters with content.
+    pub empty_book: EmptyBook,
+    /// Keep the LaTeX source of `$inline$` math in the text and of `$$` display math and `math`
+    /// code blocks as examples.
+    pub math: bool,
+    /// Collect abbreviations defined like `*[HTML]: HyperText Markup Language` and follow their
+    /// first use in every chapter with the expansion.
+    pub abbreviations: bool,
+    /// Only write the pages of split chapters whose source changed since the last build into
+    /// `output_dir`, keeping track of the pages in a manifest file there.
+    pub incremental: bool,
+    /// Render the last line of a block quote starting with an em dash or `--` as the attribution
+    /// of the quote, in italics on a line of its own.
+    pub quote_attributions: bool,
+    /// How the pages of split chapters are printed to stdout when no `output_dir` is set.
+    pub split_stdout: SplitStdout,
+    /// Put the number of numbered chapters like `1.2.` in front of the title of their section.
+    pub section_numbers: bool,
+    /// What is rendered of the prefix and suffix chapters of the book, the unnumbered chapters
+    /// before the first and after the last numbered chapter.
+    pub affix_chapters: AffixChapters,
+    /// Roff processor the pages are written for, deciding how characters outside of ASCII are
+    /// written.
+    pub target: RoffTarget,
+    /// Render a single line paragraph starting with `Table:` right before or after a table as
+    /// the caption of the table, centered in bold.
+    pub table_captions: bool,
+    /// Allow words longer than this many characters, like URLs, to be broken after every this
+    /// many characters so that they don't run past the end of the line.
+    pub break_long_words: Option<usize>,
+    /// Indentation of the content of list items in ens, wrapped lines hang at this indentation
+    /// after the marker of the item. Defaults to 4, ordered lists are indented further if their
+    /// widest marker needs it.
+    pub list_indent: Option<u8>,
+    /// Leave out the `.TH` header of the pages, or the prologue of `mdoc` pages, so that their
+    /// sections can be included in a page with a header of its own.
+    pub body_only: bool,
+    /// Move fenced code blocks tagged with `example-tag` in their info string, together with
+    /// the paragraph right before them, to an `EXAMPLES` section at the end of the page.
+    pub examples: bool,
+    /// Tag of the code blocks moved to the `EXAMPLES` section, `example` if unset.
+    pub example_tag: Option<String>,
+    /// Show the title of the book centered in bold at the top of the first section after `NAME`
+    /// when the book is rendered as a single page.
+    pub title_banner: bool,
+    /// Pages written to `output-dir` next to the rendered ones that only include another page
+    /// with `.so`, by their file name like `foo-config.5` mapped to the page they include
+    /// relative to the root of the manual, like `man5/foo.conf.5`.
+    pub aliases: BTreeMap<String, String>,
+    /// Shorten the addresses of links longer than this many characters in the text to their
+    /// start followed by `...`, the full addresses are listed in the `SEE ALSO` section.
+    pub truncate_urls: Option<usize>,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// Presentation of code blocks.
+pub enum CodeBlockStyle {
+    /// An example block (`.EX`/`.EE`).
+    #[default]
+    Example,
+    /// Plain no-fill text (`.nf`/`.fi`) without the example styling.
+    Verbatim,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// Macro package used to write the pages.
+pub enum MacroPackage {
+    /// The `man` macros understood by all implementations of `man`.
+    #[default]
+    Man,
+    /// The semantic `mdoc` macros of BSD systems.
+    Mdoc,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// Fallback for markdown elements without a roff representation, like raw HTML.
+pub enum UnhandledNodes {
+    /// The elements are left out.
+    #[default]
+    Drop,
+    /// The text of the elements is rendered as it is.
+    Text,
+    /// A roff comment naming the element is emitted in its place.
+    Comment,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// Output of a book without any chapters with content. A warning is printed either way.
+pub enum EmptyBook {
+    /// Nothing is written.
+    #[default]
+    Skip,
+    /// A single page with nothing but the header and the NAME section is written.
+    Page,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// Placement of the definitions of footnotes.
+pub enum FootnoteStyle {
+    /// The definitions are collected under `NOTES` and referenced by their number like `[1]`.
+    #[default]
+    Collect,
+    /// The definition is rendered in parentheses in place of every reference to it.
+    Inline,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// Presentation of inline code.
+pub enum InlineCodeStyle {
+    /// Italic code between backticks, like `` `\fIcode\fR` ``.
+    #[default]
+    Quoted,
+    /// Italic code without backticks.
+    Italic,
+    /// Bold code without backticks.
+    Bold,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// Roff processor the pages are written for.
+pub enum RoffTarget {
+    /// A modern `groff` reading UTF-8, characters outside of ASCII are written as they are.
+    #[default]
+    Groff,
+    /// `mandoc`, characters outside of ASCII are written as `\[uXXXX]` escapes that are read
+    /// the same in any locale.
+    Mandoc,
+    /// A traditional `troff` like Heirloom troff, characters with a classic name like the em
+    /// dash are written as `\(em` and all others as `\[uXXXX]` escapes.
+    Troff,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// Rendering of the prefix and suffix chapters of a book.
+pub enum AffixChapters {
+    /// The chapters are rendered where they are in the summary like any other chapter, without
+    /// a number in their title.
+    #[default]
+    Sections,
+    /// The chapters are left out.
+    Skip,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// Output of the pages of split chapters without an `output_dir`.
+pub enum SplitStdout {
+    /// The pages are printed one after another, each page after the first one preceded by a
+    /// comment naming the file it would be saved to.
+    #[default]
+    Separator,
+    /// Splitting chapters without an `output_dir` is an error.
+    Error,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// Presentation of tables.
+pub enum TableStyle {
+    /// A table laid out by the `tbl` preprocessor.
+    #[default]
+    Tbl,
+    /// Plain text with the columns aligned by spaces for viewers without `tbl`.
+    Plain,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// Presentation of the title of code blocks.
+pub enum CodeTitleStyle {
+    /// The title in bold.
+    #[default]
+    Bold,
+    /// The title in italic.
+    Italic,
+    /// The title in the regular font.
+    Roman,
+    /// No title at all.
+    None,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// Presentation of struck through text.
+pub enum StrikethroughStyle {
+    /// The text enclosed in `[-` and `-]`, readable on every device.
+    #[default]
+    Brackets,
+    /// Every character overstruck with a slash for devices that support overstriking.
+    Overstrike,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// Presentation of keys marked up with `<kbd>`.
+pub enum KbdStyle {
+    /// The key in bold.
+    #[default]
+    Bold,
+    /// The key enclosed in brackets like `[Ctrl]`.
+    Brackets,
+}
+
+/// Returns the lowercase words of `text` joined by dashes, like `my-tool` for `My Tool`.
+pub(crate) fn slug(text: &str) -> String {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+        .to_lowercase()
+}
+
+impl ManOutputConfiguration {
+    /// The key of the table in `book.toml` that holds this configuration.
+    pub const KEY: &'static str = "output.man";
+
+    /// Returns the section of the manual the pages belong to.
+    pub fn section_number(&self) -> u8 {
+        self.section.unwrap_or(7)
+    }
+
+    /// Returns the title of the manual, the configured `manual` or else the conventional title of
+    /// the section.
+    pub fn manual_title(&self) -> Option<&str> {
+        if let Some(manual) = &self.manual {
+            return Some(manual);
+        }
+        let title = match self.section_number() {
+            1 => "User Commands",
+            2 => "System Calls",
+            3 => "Library Functions",
+            4 => "Special Files",
+            5 => "File Formats",
+            6 => "Games",
+            7 => "Miscellaneous",
+            8 => "System Administration",
+            _ => return None,
+        };
+        Some(title)
+    }
+
+    /// Returns the tag of the code blocks moved to the `EXAMPLES` section.
+    pub fn example_tag(&self) -> &str {
+        self.example_tag.as_deref().unwrap_or("example")
+    }
+
+    /// Returns true if the chapter `name` should be rendered as the `SYNOPSIS` section.
+    pub fn is_synopsis_chapter(&self, name: &str) -> bool {
+        self.synopsis_chapter
+            .as_deref()
+            .unwrap_or("Synopsis")
+            .eq_ignore_ascii_case(name.trim())
+    }
+
+    /// Returns the name of the file a combined page is saved to, the configured `filename` or
+    /// else the slug of the book `title` like `my-tool.man`.
+    pub fn combined_filename(&self, title: Option<&str>) -> String {
+        if let Some(filename) = &self.filename {
+            return filename.clone();
+        }
+        let slug = slug(title.unwrap_or_default());
+        if slug.is_empty() {
+            "book.man".into()
+        } else {
+            format!("{}.man", slug)
+        }
+    }
+
+    /// Checks that the configured values are valid and can be used together. The returned
+    /// [`Error::Config`](Error::Config) names the offending key.
+    pub fn validate(&self) -> Result<(), Error> {
+        let key = |name: &str| format!("{}.{}", Self::KEY, name);
+
+        if let Some(filename) = &self.filename {
+            if self.output_dir.is_none() {
+                return Err(Error::config(
+                    key("filename"),
+                    "can only be used together with `output-dir`",
+                ));
+            }
+            if self.split_chapters {
+                return Err(Error::config(
+                    key("filename"),
+                    "can't be used together with `split-chapters`",
+                ));
+            }
+            if filename.is_empty() || filename.contains(['/', '\\']) {
+                return Err(Error::config(
+                    key("filename"),
+                    "must be the name of a file without any directories",
+                ));
+            }
+        }
+        if !self.aliases.is_empty() && self.output_dir.is_none() {
+            return Err(Error::config(
+                key("aliases"),
+                "can only be used together with `output-dir`",
+            ));
+        }
+        for (alias, target) in &self.aliases {
+            if alias.is_empty() || alias.contains(['/', '\\']) {
+                return Err(Error::config(
+                    key("aliases"),
+                    format!(
+                        "`{}` must be the name of a file without any directories",
+                        alias
+                    ),
+                ));
+            }
+            if target.trim().is_empty() {
+                return Err(Error::config(
+                    key("aliases"),
+                    format!("`{}` must name the page it includes", alias),
+                ));
+            }
+        }
+        if self.split_chapters
+            && self.output_dir.is_none()
+            && self.split_stdout == SplitStdout::Error
+        {
+            return Err(Error::config(
+                key("split-stdout"),
+                "pages of split chapters can't be told apart on stdout, set `output-dir` to write \
+                 them to files",
+            ));
+        }
+        if matches!(self.code_wrap_width, Some(width) if width < 2) {
+            return Err(Error::config(
+                key("code-wrap-width"),
+                "must be at least 2 columns",
+            ));
+        }
+        if self.break_long_words == Some(0) {
+            return Err(Error::config(
+                key("break-long-words"),
+                "must be greater than 0",
+            ));
+        }
+        if matches!(&self.example_tag, Some(tag) if tag.is_empty() || tag.contains([',', ' '])) {
+            return Err(Error::config(
+                key("example-tag"),
+                "must be a single word of the info string",
+            ));
+        }
+        if self.truncate_urls == Some(0) {
+            return Err(Error::config(
+                key("truncate-urls"),
+                "must be greater than 0",
+            ));
+        }
+        if self.list_indent == Some(0) {
+            return Err(Error::config(key("list-indent"), "must be greater than 0"));
+        }
+        if self.line_length == Some(0) {
+            return Err(Error::config(key("line-length"), "must be greater than 0"));
+        }
+        if matches!(self.section, Some(section) if !(1..=9).contains(&section)) {
+            return Err(Error::config(key("section"), "must be between 1 and 9"));
+        }
+        if matches!(self.index_section, Some(section) if !(1..=9).contains(&section)) {
+            return Err(Error::config(
+                key("index-section"),
+                "must be between 1 and 9",
+            ));
+        }
+        if self.max_depth == Some(0) {
+            return Err(Error::config(key("max-depth"), "must be greater than 0"));
+        }
+        if let ChapterOrder::Explicit(names) = &self.chapter_order {
+            let duplicate = names
+                .iter()
+                .enumerate()
+                .find(|(i, name)| names[..*i].contains(name));
+            if let Some((_, name)) = duplicate {
+                return Err(Error::config(
+                    key("chapter-order"),
+                    format!("chapter `{}` is listed more than once", name),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Loads the configuration from the `RenderContext` falling back to the default if the
+    /// `output.man` table is missing.
+    ///
+    /// The options of the table `output.man.profiles.<name>` of the selected `profile` take
+    /// precedence over the other options of `output.man`. Environment variables prefixed with
+    /// `MDBOOK_MAN_` take precedence over both, `MDBOOK_MAN_SPLIT_CHAPTERS=true` overrides
+    /// `split-chapters` and `MDBOOK_MAN_PROFILE` selects the profile. Their values are read as
+    /// booleans or integers where the option takes one and as strings otherwise. The loaded
+    /// configuration is [validated](ManOutputConfiguration::validate).
+    pub fn load(ctx: &RenderContext) -> Result<Self, Error> {
+        let mut config = ctx.config.clone();
+        let overrides: Vec<_> = env::vars()
+            .filter_map(|(name, value)| match name.strip_prefix(ENV_PREFIX) {
+                Some(option) if !option.is_empty() => {
+                    Some((option.to_lowercase().replace('_', "-"), name, value))
+                }
+                _ => None,
+            })
+            .collect();
+
+        // the options of the profile are overridden by the environment in turn
+        let profile = match overrides.iter().find(|(option, ..)| option == "profile") {
+            Some((.., profile)) => Some(profile.clone()),
+            None => config
+                .get(&format!("{}.profile", Self::KEY))
+                .and_then(|profile| profile.as_str())
+                .map(str::to_string),
+        };
+        if let Some(profile) = profile {
+            let key = format!("{}.profiles.{}", Self::KEY, profile);
+            let options = config
+                .get(&key)
+                .and_then(|options| options.as_table())
+                .cloned()
+                .ok_or_else(|| {
+                    Error::config(
+                        format!("{}.profile", Self::KEY),
+                        format!("there is no `{}` table", key),
+                    )
+                })?;
+            for (option, value) in options {
+                let key = format!("{}.{}", Self::KEY, option);
+                config
+                    .set(&key, value)
+                    .map_err(|e| Error::config(&key, e.to_string()))?;
+            }
+        }
+
+        // the error of serde names the invalid field, unlike the one of `get_deserialized_opt`
+        let deserialize = |config: &mdbook::Config| match config.get(Self::KEY) {
+            Some(table) => table.clone().try_into(),
+            None => Ok(Self::default()),
+        };
+        let mut loaded =
+            deserialize(&config).map_err(|e| Error::config(Self::KEY, e.to_string()))?;
+        for (option, name, value) in overrides {
+            let key = format!("{}.{}", Self::KEY, option);
+            let invalid =
+                |e: &dyn fmt::Display| Error::config(&key, format!("{} (set by `{}`)", e, name));
+            // the value is read as a string if the option doesn't take a boolean or an integer
+            let coerced = match value.as_str() {
+                "true" => Some(toml::Value::Boolean(true)),
+                "false" => Some(toml::Value::Boolean(false)),
+                _ => value.parse().ok().map(toml::Value::Integer),
+            };
+            if let Some(coerced) = coerced {
+                let mut attempt = config.clone();
+                attempt.set(&key, coerced).map_err(|e| invalid(&e))?;
+                if let Ok(attempted) = deserialize(&attempt) {
+                    config = attempt;
+                    loaded = attempted;
+                    continue;
+                }
+            }
+            config.set(&key, value).map_err(|e| invalid(&e))?;
+            loaded = deserialize(&config).map_err(|e| invalid(&e))?;
+        }
+
+        loaded.validate()?;
+        Ok(loaded)
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// Separator inserted between chapters of a combined man page.
+pub enum ChapterSeparator {
+    /// Chapters are only separated by their section headers.
+    #[default]
+    None,
+    /// A horizontal rule spanning the whole line.
+    Rule,
+    /// Additional vertical space.
+    Space,
+    /// A roff comment that is not visible in the rendered page.
+    Comment,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// Handling of chapters rendered in a section with the same title as an earlier one.
+pub enum DuplicateSections {
+    /// The title of every later section is followed by its count, like `Usage (2)`.
+    #[default]
+    Number,
+    /// The title is followed by the title of the part the chapter is in, like `Usage (Guide)`.
+    /// Chapters that aren't in a part of their own are numbered.
+    Part,
+    /// The chapter is appended to the earlier section.
+    Merge,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+/// Selects a single chapter of the book.
+pub enum ChapterSelector {
+    /// The zero based position of the chapter in the book.
+    Index(usize),
+    /// The name of the chapter.
+    Name(String),
+}
+
+impl fmt::Display for ChapterSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChapterSelector::Index(index) => write!(f, "{}", index),
+            ChapterSelector::Name(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "ChapterOrderRepr", into = "ChapterOrderRepr")]
+/// Order in which the chapters are rendered.
+pub enum ChapterOrder {
+    /// The order of `SUMMARY.md`.
+    #[default]
+    Summary,
+    /// Sorted by the chapter name.
+    Alphabetical,
+    /// The listed chapters come first in the given order followed by the rest of the chapters
+    /// in the order of `SUMMARY.md`.
+    Explicit(Vec<String>),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum ChapterOrderRepr {
+    Named(String),
+    Explicit(Vec<String>),
+}
+
+impl TryFrom<ChapterOrderRepr> for ChapterOrder {
+    type Error = String;
+
+    fn try_from(repr: ChapterOrderRepr) -> Result<Self, Self::Error> {
+        match repr {
+            ChapterOrderRepr::Named(name) => match name.as_str() {
+                "summary" => Ok(ChapterOrder::Summary),
+                "alphabetical" => Ok(ChapterOrder::Alphabetical),
+                _ => Err(format!(
+                    "unknown chapter order `{}`, expected `summary`, `alphabetical` or a list of chapter names",
+                    name
+                )),
+            },
+            ChapterOrderRepr::Explicit(names) => Ok(ChapterOrder::Explicit(names)),
+        }
+    }
+}
+
+impl From<ChapterOrder> for ChapterOrderRepr {
+    fn from(order: ChapterOrder) -> Self {
+        match order {
+            ChapterOrder::Summary => ChapterOrderRepr::Named("summary".into()),
+            ChapterOrder::Alphabetical => ChapterOrderRepr::Named("alphabetical".into()),
+            ChapterOrder::Explicit(names) => ChapterOrderRepr::Explicit(names),
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use crate::LintIssue;
+
+use roffman::RoffError;
+
+use std::{fmt, io};
+
+#[derive(Debug)]
+/// An error type returned by the functions of this crate.
+pub enum Error {
+    /// A value of the `output.man` configuration is invalid. `key` is the full path of the
+    /// offending key, for example `output.man.section`.
+    Config { key: String, message: String },
+    /// A value in the front matter of `chapter` is invalid. `key` is the key in the front matter,
+    /// for example `section`.
+    FrontMatter {
+        chapter: String,
+        key: String,
+        message: String,
+    },
+    /// A selected chapter doesn't exist in the book.
+    ChapterNotFound { selector: String, count: usize },
+    /// The `RenderContext` passed by mdBook could not be parsed.
+    Context(String),
+    /// Rendering the roff failed.
+    Render(RoffError),
+    /// Writing the output failed.
+    Io(io::Error),
+    /// The rendered page failed the checks enabled with `check`.
+    Lint(Vec<LintIssue>),
+    /// A chapter contains an element without a roff representation while `strict` is enabled.
+    Unhandled {
+        chapter: String,
+        element: String,
+        line: usize,
+    },
+}
+
+impl Error {
+    pub(crate) fn config(key: impl Into<String>, message: impl Into<String>) -> Self {
+        Error::Config {
+            key: key.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Config { key, message } => write!(f, "invalid value of `{}` - {}", key, message),
+            Error::FrontMatter {
+                chapter,
+                key,
+                message,
+            } => write!(
+                f,
+                "invalid value of `{}` in the front matter of chapter `{}` - {}",
+                key, chapter, message
+            ),
+            Error::ChapterNotFound { selector, count } => write!(
+                f,
+                "chapter `{}` not found, the book has {} chapters",
+                selector, count
+            ),
+            Error::Context(err) => write!(f, "failed to parse the render context - `{}`", err),
+            Error::Render(err) => write!(f, "{}", err),
+            Error::Io(err) => write!(f, "failed to write output - `{}`", err),
+            Error::Lint(issues) => {
+                write!(f, "rendered page is malformed")?;
+                for issue in issues {
+                    write!(f, "\n  {}", issue)?;
+                }
+                Ok(())
+            }
+            Error::Unhandled {
+                chapter,
+                element,
+                line,
+            } => write!(
+                f,
+                "chapter `{}` contains an unsupported {} on line {}",
+                chapter, element, line
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Render(err) => Some(err),
+            Error::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<RoffError> for Error {
+    fn from(err: RoffError) -> Self {
+        Error::Render(err)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
@@ -0,0 +1,83 @@
+//! Escaping of characters that roffman passes through unchanged but that have a special meaning
+//! in roff, and of characters the targeted roff processor may not read as they are.
+
+use crate::RoffTarget;
+
+use std::borrow::Cow;
+
+/// Escapes `text` of a code block or an inline code span so that it renders as written.
+/// Backslashes are replaced with `\e`, while empty lines and lines starting with the no-break
+/// control character `'` are prefixed with a zero-width `\&` so that they are neither dropped
+/// nor interpreted.
+pub(crate) fn code(text: &str) -> String {
+    let text = text.replace('\\', "\\e");
+    let mut out = String::with_capacity(text.len());
+    let mut lines = text.split('\n').peekable();
+    let mut first = true;
+    while let Some(line) = lines.next() {
+        if !first {
+            out.push('\n');
+        }
+        first = false;
+        // the part after a trailing newline is not a line of its own
+        let is_last = lines.peek().is_none();
+        if line.starts_with('\'') || (line.trim().is_empty() && !is_last) {
+            out.push_str("\\&");
+        }
+        out.push_str(line);
+    }
+    out
+}
+
+/// Characters with a name of their own in classic troff and its descendants.
+const GLYPHS: &[(char, &str)] = &[
+    ('\u{a0}', "\\ "),
+    ('\u{a7}', "\\(sc"),
+    ('\u{a9}', "\\(co"),
+    ('\u{ae}', "\\(rg"),
+    ('\u{b0}', "\\(de"),
+    ('\u{b1}', "\\(+-"),
+    ('\u{d7}', "\\(mu"),
+    ('\u{f7}', "\\(di"),
+    ('\u{2013}', "\\(en"),
+    ('\u{2014}', "\\(em"),
+    ('\u{2018}', "\\(oq"),
+    ('\u{2019}', "\\(cq"),
+    ('\u{201c}', "\\(lq"),
+    ('\u{201d}', "\\(rq"),
+    ('\u{2020}', "\\(dg"),
+    ('\u{2022}', "\\(bu"),
+    ('\u{2190}', "\\(<-"),
+    ('\u{2192}', "\\(->"),
+    ('\u{221e}', "\\(if"),
+    ('\u{2260}', "\\(!="),
+    ('\u{2264}', "\\(<="),
+    ('\u{2265}', "\\(>="),
+];
+
+/// Escapes the characters outside of ASCII in the rendered `page` the way the `target` reads
+/// them. Comment lines are left as they are.
+pub(crate) fn non_ascii(page: &str, target: RoffTarget) -> Cow<'_, str> {
+    if target == RoffTarget::Groff || page.is_ascii() {
+        return Cow::Borrowed(page);
+    }
+    let mut out = String::with_capacity(page.len());
+    for line in page.split_inclusive('\n') {
+        if line.starts_with(".\\\"") || line.is_ascii() {
+            out.push_str(line);
+            continue;
+        }
+        for c in line.chars() {
+            let glyph = match target {
+                RoffTarget::Troff => GLYPHS.iter().find(|(glyph, _)| *glyph == c),
+                _ => None,
+            };
+            match glyph {
+                Some((_, name)) => out.push_str(name),
+                None if c.is_ascii() => out.push(c),
+                None => out.push_str(&format!("\\[u{:04X}]", c as u32)),
+            }
+        }
+    }
+    Cow::Owned(out)
+}
@@ -0,0 +1,148 @@
+//! Options of single chapters set in the YAML front matter starting their source.
+//!
+//! Only flat `key: value` pairs are read, other keys are left for the tools they are meant for.
+
+use crate::{Error, ManOutputConfiguration};
+
+use mdbook::book::Chapter;
+
+use std::borrow::Cow;
+
+/// Line opening and closing the front matter.
+const DELIMITER: &str = "---";
+
+/// Returns true if `line` of a front matter is a `key: value` pair, a comment or continues the
+/// value of the key before it, like the items of a list.
+fn matter_line(line: &str) -> bool {
+    if line.trim().is_empty() || line.starts_with(char::is_whitespace) || line.starts_with('#') {
+        return true;
+    }
+    match line.split_once(':') {
+        Some((key, value)) => {
+            !key.is_empty()
+                && key
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+                && (value.is_empty() || value.starts_with(char::is_whitespace))
+        }
+        None => false,
+    }
+}
+
+/// Splits `content` into its front matter and the markdown following it. The content only starts
+/// with a front matter if the first line after the opening `---` isn't blank and every line up to
+/// the closing one is a `key: value` pair, so that a thematic break opening the chapter isn't
+/// taken for one.
+pub(crate) fn split(content: &str) -> (Option<&str>, &str) {
+    let rest = match content.strip_prefix(DELIMITER) {
+        Some(rest) if rest.starts_with('\n') || rest.starts_with("\r\n") => {
+            &rest[rest.find('\n').unwrap_or_default() + 1..]
+        }
+        _ => return (None, content),
+    };
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        let trimmed = line.trim_end();
+        if offset == 0 && (trimmed.is_empty() || trimmed.starts_with(char::is_whitespace)) {
+            break;
+        }
+        if offset > 0 && trimmed == DELIMITER {
+            let matter = &rest[..offset];
+            return (Some(matter), &rest[offset + line.len()..]);
+        }
+        if !matter_line(trimmed) {
+            break;
+        }
+        offset += line.len();
+    }
+    (None, content)
+}
+
+/// Returns the markdown of `content` with the lines of its front matter left blank, so that the
+/// lines of the markdown keep their numbers.
+pub(crate) fn strip(content: &str) -> Cow<'_, str> {
+    let markdown = split(content).1;
+    let matter = &content[..content.len() - markdown.len()];
+    if matter.is_empty() {
+        return Cow::Borrowed(content);
+    }
+    Cow::Owned(format!(
+        "{}{}",
+        "\n".repeat(matter.matches('\n').count()),
+        markdown
+    ))
+}
+
+/// Options of a chapter that take precedence over the `output.man` configuration.
+#[derive(Debug, Default)]
+pub(crate) struct FrontMatter {
+    /// Section of the manual the page of the chapter belongs to.
+    pub(crate) section: Option<u8>,
+    /// Name of the page in its header.
+    pub(crate) page_name: Option<String>,
+    /// Whether the chapter is rendered as a page of its own when splitting chapters.
+    pub(crate) man_page: Option<bool>,
+    /// Name of the file the page is saved to.
+    pub(crate) filename: Option<String>,
+    /// Title of the section the chapter is rendered in.
+    pub(crate) section_title: Option<String>,
+}
+
+impl FrontMatter {
+    /// Reads the options from the front matter of the `chapter`.
+    pub(crate) fn of(chapter: &Chapter) -> Result<Self, Error> {
+        let mut matter = Self::default();
+        let text = match split(&chapter.content).0 {
+            Some(text) => text,
+            None => return Ok(matter),
+        };
+        let invalid = |key: &str, message: &str| Error::FrontMatter {
+            chapter: chapter.name.clone(),
+            key: key.to_string(),
+            message: message.to_string(),
+        };
+
+        for line in text.lines() {
+            let (key, value) = match line.split_once(':') {
+                Some((key, value)) if !line.starts_with(char::is_whitespace) => (key.trim(), value),
+                _ => continue,
+            };
+            let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+            match key {
+                "section" => {
+                    let section = value
+                        .parse()
+                        .ok()
+                        .filter(|section| (1..=9).contains(section))
+                        .ok_or_else(|| invalid(key, "must be between 1 and 9"))?;
+                    matter.section = Some(section);
+                }
+                "page-name" => matter.page_name = Some(value.to_string()),
+                "filename" => {
+                    if value.is_empty() || value.contains(['/', '\\']) {
+                        return Err(invalid(key, "must be a file name without a directory"));
+                    }
+                    matter.filename = Some(value.to_string());
+                }
+                "section-title" => matter.section_title = Some(value.to_string()),
+                "man-page" => {
+                    let man_page = value
+                        .parse()
+                        .map_err(|_| invalid(key, "must be `true` or `false`"))?;
+                    matter.man_page = Some(man_page);
+                }
+                _ => {}
+            }
+        }
+        Ok(matter)
+    }
+
+    /// Returns the configuration of the chapter, `cfg` with the options of the front matter.
+    pub(crate) fn apply(&self, cfg: &ManOutputConfiguration) -> ManOutputConfiguration {
+        let mut cfg = cfg.clone();
+        if let Some(section) = self.section {
+            cfg.section = Some(section);
+        }
+        cfg
+    }
+}
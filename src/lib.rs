@@ -1,19 +1,617 @@
 extern crate mdbook;
 
+mod config;
+mod error;
+mod escape;
+mod frontmatter;
+mod links;
+mod lint;
+mod math;
+mod mdoc;
+mod raw;
+mod table;
+
+pub use config::{
+    AffixChapters, ChapterOrder, ChapterSelector, ChapterSeparator, CodeBlockStyle, CodeTitleStyle,
+    DuplicateSections, EmptyBook, FootnoteStyle, InlineCodeStyle, KbdStyle, MacroPackage,
+    ManOutputConfiguration, RoffTarget, SplitStdout, StrikethroughStyle, TableStyle,
+    UnhandledNodes,
+};
+pub use error::Error;
+pub use lint::{lint_page, LintIssue};
+
+// Types of the crates this crate is built on that are part of its public API. They are
+// re-exported so that they can be used without depending on matching versions of `mdbook`,
+// `comrak` and `roffman`.
+pub use comrak::nodes::NodeValue;
+pub use mdbook::renderer::RenderContext;
+pub use roffman::{Roff, RoffError, RoffNode};
+
 use comrak::{
-    nodes::{AstNode, NodeValue},
+    nodes::{Ast, AstNode, ListDelimType, ListType, NodeCodeBlock},
     parse_document, Arena, ComrakOptions,
 };
-use mdbook::{renderer::RenderContext, BookItem};
-use roffman::{IntoRoffNode, Roff, RoffNode, Roffable, SectionNumber};
+use frontmatter::FrontMatter;
+use links::{ChapterLinks, ReferenceStyle};
+use mdbook::{
+    book::{Book, Chapter},
+    BookItem,
+};
+use roffman::{FontStyle, IntoRoffNode, Roffable, SectionNumber};
+
+use std::{borrow::Cow, cell::RefCell, io, iter, path::PathBuf};
 
+/// Calls `f` on every node of the tree, once when `entering` the node and once when leaving it
+/// after all of its children were visited. Children of a node are skipped if `f` returns false
+/// when entering it. Nodes nested deeper than the maximum depth of the parser are skipped.
 fn iter_nodes<'a, F>(node: &'a AstNode<'a>, out: &mut Parser, f: &F)
 where
-    F: Fn(&'a AstNode<'a>, &mut Parser),
+    F: Fn(&'a AstNode<'a>, &mut Parser, bool) -> bool,
 {
-    f(node, out);
+    if out.depth >= out.max_depth {
+        out.truncated = true;
+        return;
+    }
+    if f(node, out, true) {
+        out.depth += 1;
+        for c in node.children() {
+            iter_nodes(c, out, f);
+        }
+        out.depth -= 1;
+        f(node, out, false);
+    }
+}
+
+/// Collects the plain text content of `node` and all of its children.
+fn text_content<'a>(node: &'a AstNode<'a>, out: &mut String) {
+    match &node.data.borrow().value {
+        NodeValue::Text(text) => out.push_str(&String::from_utf8_lossy(text)),
+        NodeValue::Code(code) => {
+            let literal = math::inline_source(&code.literal).unwrap_or(&code.literal);
+            out.push_str(&String::from_utf8_lossy(literal))
+        }
+        NodeValue::SoftBreak | NodeValue::LineBreak => out.push(' '),
+        _ => {}
+    }
+    for c in node.children() {
+        text_content(c, out);
+    }
+}
+
+/// Collects the content of the heading `node` like [`text_content`](text_content) for the
+/// title of a section, with inline code styled like in the body. The font of the title is
+/// restored after the code.
+fn title_content<'a>(node: &'a AstNode<'a>, cfg: &ManOutputConfiguration, out: &mut String) {
+    match &node.data.borrow().value {
+        // the source of inline math is set in roman like in the body
+        NodeValue::Code(code) if math::inline_source(&code.literal).is_some() => {
+            let source = math::inline_source(&code.literal).unwrap_or_default();
+            let source = escape::code(&String::from_utf8_lossy(source));
+            out.push_str(&format!("\\fR{}\\fP", source));
+            return;
+        }
+        NodeValue::Code(code) => {
+            let code = escape::code(&String::from_utf8_lossy(&code.literal));
+            match cfg.inline_code_style {
+                InlineCodeStyle::Quoted => out.push_str(&format!("`\\fI{}\\fP`", code)),
+                InlineCodeStyle::Italic => out.push_str(&format!("\\fI{}\\fP", code)),
+                InlineCodeStyle::Bold => out.push_str(&format!("\\fB{}\\fP", code)),
+            }
+            return;
+        }
+        NodeValue::Text(text) => out.push_str(&String::from_utf8_lossy(text)),
+        NodeValue::SoftBreak | NodeValue::LineBreak => out.push(' '),
+        _ => {}
+    }
     for c in node.children() {
-        iter_nodes(c, out, f);
+        title_content(c, cfg, out);
+    }
+}
+
+/// Returns the number the ordered `list` starts at. With `continue_ordered_lists` a list starting
+/// at 1 picks up where the previous ordered list ended if only loose content is in between.
+fn list_start<'a>(list: &'a AstNode<'a>, cfg: &ManOutputConfiguration) -> usize {
+    let start = match &list.data.borrow().value {
+        NodeValue::List(list) => list.start,
+        _ => 1,
+    };
+    if !cfg.continue_ordered_lists || start != 1 {
+        return start;
+    }
+
+    let mut previous = list.previous_sibling();
+    while let Some(node) = previous {
+        match &node.data.borrow().value {
+            NodeValue::Paragraph | NodeValue::CodeBlock(_) | NodeValue::HtmlBlock(_) => {}
+            NodeValue::List(list) if list.list_type == ListType::Ordered => {
+                return list_start(node, cfg) + node.children().count();
+            }
+            _ => break,
+        }
+        previous = node.previous_sibling();
+    }
+    start
+}
+
+/// Returns the marker of the `item` like `3.` if it is part of an ordered list.
+fn ordered_marker<'a>(item: &'a AstNode<'a>, cfg: &ManOutputConfiguration) -> Option<String> {
+    let list = item.parent()?;
+    let delimiter = match &list.data.borrow().value {
+        NodeValue::List(list) if list.list_type == ListType::Ordered => match list.delimiter {
+            ListDelimType::Period => '.',
+            ListDelimType::Paren => ')',
+        },
+        _ => return None,
+    };
+    let mut number = list_start(list, cfg);
+    let mut previous = item.previous_sibling();
+    while let Some(node) = previous {
+        number += 1;
+        previous = node.previous_sibling();
+    }
+    Some(format!("{}{}", number, delimiter))
+}
+
+/// Marker of the items of bullet lists.
+const BULLET: &str = "\\(bu";
+
+/// Returns true if the `item` is part of a bullet list and isn't a task list item, whose marker
+/// takes the place of the bullet.
+fn bullet_item<'a>(item: &'a AstNode<'a>) -> bool {
+    let bullet = item.parent().is_some_and(|list| {
+        matches!(&list.data.borrow().value, NodeValue::List(list) if list.list_type == ListType::Bullet)
+    });
+    let task = item
+        .first_child()
+        .and_then(|paragraph| paragraph.first_child())
+        .is_some_and(|node| matches!(node.data.borrow().value, NodeValue::TaskItem(_)));
+    bullet && !task
+}
+
+/// Returns the marker the content of the list `item` is rendered next to, the number of an
+/// ordered list item or a bullet.
+fn item_marker<'a>(item: &'a AstNode<'a>, cfg: &ManOutputConfiguration) -> Option<String> {
+    ordered_marker(item, cfg).or_else(|| bullet_item(item).then(|| BULLET.to_string()))
+}
+
+/// Returns true if the `list` is nested in a list item or the details of a description, it is
+/// indented under the content it is part of.
+fn nested_list<'a>(list: &'a AstNode<'a>) -> bool {
+    in_details(list)
+        || list
+            .parent()
+            .is_some_and(|item| matches!(item.data.borrow().value, NodeValue::Item(_)))
+}
+
+/// Returns true if the block `node` directly follows a list that isn't nested, whose indentation
+/// would otherwise carry over to the block.
+fn follows_list<'a>(node: &'a AstNode<'a>) -> bool {
+    node.previous_sibling().is_some_and(|list| {
+        matches!(list.data.borrow().value, NodeValue::List(_)) && !nested_list(list)
+    })
+}
+
+/// Smallest indentation of the content of list items unless `list_indent` is set.
+const ITEM_INDENT: u8 = 4;
+
+/// Returns the indentation of the content of the list `item`, at least the configured
+/// `list_indent`. The content of all items of an ordered list lines up after the widest marker
+/// of the list.
+fn item_indent<'a>(item: &'a AstNode<'a>, cfg: &ManOutputConfiguration) -> u8 {
+    let indent = cfg.list_indent.unwrap_or(ITEM_INDENT);
+    let widest = item
+        .parent()
+        .and_then(|list| list.last_child())
+        .and_then(|last| ordered_marker(last, cfg));
+    match widest {
+        // the marker needs a space after it to fit in front of the content
+        // markers of numbers up to `usize::MAX` are at most 21 characters wide
+        Some(marker) => indent.max(marker.chars().count() as u8 + 1),
+        None => indent,
+    }
+}
+
+/// Returns true if the `paragraph` is the first one of a list item with a marker or the details
+/// of a description, its content is rendered next to the marker or term.
+fn leads_item<'a>(paragraph: &'a AstNode<'a>) -> bool {
+    if paragraph.previous_sibling().is_some() {
+        return false;
+    }
+    let item = match paragraph.parent() {
+        Some(item) => item,
+        None => return false,
+    };
+    if matches!(item.data.borrow().value, NodeValue::DescriptionDetails) {
+        return true;
+    }
+    bullet_item(item)
+        || item.parent().is_some_and(|list| {
+            matches!(&list.data.borrow().value, NodeValue::List(list) if list.list_type == ListType::Ordered)
+        })
+}
+
+/// Returns true if the `paragraph` continues a list item after its first block, it is indented
+/// to line up with the content of the item.
+fn continues_item<'a>(paragraph: &'a AstNode<'a>) -> bool {
+    paragraph.previous_sibling().is_some()
+        && paragraph
+            .parent()
+            .is_some_and(|item| matches!(item.data.borrow().value, NodeValue::Item(_)))
+}
+
+/// Abbreviation defined like `*[HTML]: HyperText Markup Language`.
+#[derive(Debug)]
+struct Abbreviation {
+    name: String,
+    expansion: String,
+    /// Set once the abbreviation was expanded on its first use.
+    used: bool,
+}
+
+/// Collects the abbreviations defined in `text`, returns them with the text with their definition
+/// lines left blank so that lines keep their numbers. Lines of fenced code blocks are skipped.
+fn abbreviations(text: &str) -> (Vec<Abbreviation>, String) {
+    let mut found = vec![];
+    let mut fence: Option<&str> = None;
+    let mut out = String::with_capacity(text.len());
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim();
+        match fence {
+            Some(marker) if trimmed.starts_with(marker) => fence = None,
+            Some(_) => {}
+            None if trimmed.starts_with("```") => fence = Some("```"),
+            None if trimmed.starts_with("~~~") => fence = Some("~~~"),
+            None => {
+                let definition = trimmed
+                    .strip_prefix("*[")
+                    .and_then(|rest| rest.split_once("]:"))
+                    .filter(|(name, _)| !name.trim().is_empty());
+                if let Some((name, expansion)) = definition {
+                    found.push(Abbreviation {
+                        name: name.trim().to_string(),
+                        expansion: expansion.trim().to_string(),
+                        used: false,
+                    });
+                    out.push_str(&line[line.trim_end().len()..]);
+                    continue;
+                }
+            }
+        }
+        out.push_str(line);
+    }
+    (found, out)
+}
+
+/// Returns the byte offset of the first occurrence of `word` in `text` that isn't part of a
+/// longer word.
+fn find_word(text: &str, word: &str) -> Option<usize> {
+    text.match_indices(word).map(|(i, _)| i).find(|&i| {
+        let before = text[..i].chars().next_back();
+        let after = text[i + word.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+/// Replaces reference images like `![alt][label]` left in `text` because their reference isn't
+/// defined with their alternative text.
+fn unresolved_images(text: &str) -> Cow<'_, str> {
+    if !text.contains("![") {
+        return Cow::Borrowed(text);
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("![") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let alt = match after.find(']') {
+            Some(end) if !after[..end].contains('[') => &after[..end],
+            _ => {
+                out.push_str("![");
+                rest = after;
+                continue;
+            }
+        };
+        out.push_str(alt);
+        rest = &after[alt.len() + 1..];
+        // the label of full and collapsed references
+        if let Some(label) = rest.strip_prefix('[') {
+            if let Some(end) = label.find(']').filter(|end| !label[..*end].contains('[')) {
+                rest = &label[end + 1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    Cow::Owned(out)
+}
+
+/// Returns true if `node` is part of the content of a link or an image.
+fn inside_link<'a>(node: &'a AstNode<'a>) -> bool {
+    node.ancestors().skip(1).any(|node| {
+        matches!(
+            node.data.borrow().value,
+            NodeValue::Link(_) | NodeValue::Image(_)
+        )
+    })
+}
+
+/// Returns true if the `paragraph` continues the details of a description after their first
+/// block.
+fn continues_details<'a>(paragraph: &'a AstNode<'a>) -> bool {
+    paragraph.previous_sibling().is_some() && in_details(paragraph)
+}
+
+/// Returns true if the block `node` is part of the details of a description or an option list
+/// item, which are indented under their term.
+fn in_details<'a>(node: &'a AstNode<'a>) -> bool {
+    node.parent().is_some_and(|parent| {
+        matches!(parent.data.borrow().value, NodeValue::DescriptionDetails)
+            || option_flag(parent).is_some()
+    })
+}
+
+/// Returns the number of characters of the escape sequence at the start of `chars`, which
+/// starts with a backslash, like `\(sl`, `\fB`, `\f[CR]` or `\o'a\(sl'`.
+fn escape_len(chars: &[char]) -> usize {
+    // the argument of escapes taking a name, like `\fB`, `\f(CR` or `\f[CR]`
+    let name = |rest: &[char]| match rest.first() {
+        Some('(') => 3.min(rest.len()),
+        Some('[') => rest
+            .iter()
+            .position(|&c| c == ']')
+            .map_or(rest.len(), |i| i + 1),
+        Some(_) => 1,
+        None => 0,
+    };
+    match chars.get(1) {
+        Some('(') => 4.min(chars.len()),
+        Some('[') => name(&chars[1..]) + 1,
+        Some('f' | '*' | 'n' | 'F' | 'm' | 'g' | 'Y') => name(&chars[2..]) + 2,
+        // escapes with a delimited argument like `\o'ab'`
+        Some('o' | 'w' | 'h' | 'v' | 'l' | 'L' | 'b' | 'D' | 'X' | 'Z' | 'N')
+            if chars.get(2) == Some(&'\'') =>
+        {
+            chars[3..]
+                .iter()
+                .position(|&c| c == '\'')
+                .map_or(chars.len(), |i| i + 4)
+        }
+        Some(_) => 2,
+        None => 1,
+    }
+}
+
+/// Inserts a zero-width break point `\:` into every word of `text` after each `width`
+/// characters, so that words longer than a line like URLs can be broken. Escape sequences count
+/// as a single character and are never split. A `width` of 0 leaves the text as it is.
+fn break_words(text: &str, width: usize) -> Cow<'_, str> {
+    if width == 0
+        || !text
+            .split_whitespace()
+            .any(|word| word.chars().count() > width)
+    {
+        return Cow::Borrowed(text);
+    }
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len() + text.len() / width * 2);
+    let mut run = 0;
+    let mut i = 0;
+    while i < chars.len() {
+        let len = if chars[i] == '\\' {
+            escape_len(&chars[i..])
+        } else {
+            1
+        };
+        if chars[i].is_whitespace() {
+            run = 0;
+        } else {
+            if run >= width {
+                out.push_str("\\:");
+                run = 0;
+            }
+            run += 1;
+        }
+        out.extend(&chars[i..i + len]);
+        i += len;
+    }
+    Cow::Owned(out)
+}
+
+/// Returns the definition of the footnote `number` referenced by `reference`.
+fn footnote_definition<'a>(reference: &'a AstNode<'a>, number: &[u8]) -> Option<&'a AstNode<'a>> {
+    let root = reference.ancestors().last()?;
+    root.children()
+        .find(|node| match &node.data.borrow().value {
+            NodeValue::FootnoteDefinition(name) => name == number,
+            _ => false,
+        })
+}
+
+/// Prefix of a paragraph taken as the caption of the table next to it.
+const CAPTION_PREFIX: &str = "Table:";
+
+/// Returns the caption of a table if the `paragraph` right before or after it is a single line
+/// starting with `Table:`.
+fn table_caption<'a>(paragraph: &'a AstNode<'a>) -> Option<String> {
+    let is_table = |node: &'a AstNode<'a>| matches!(node.data.borrow().value, NodeValue::Table(_));
+    if !paragraph.previous_sibling().is_some_and(is_table)
+        && !paragraph.next_sibling().is_some_and(is_table)
+    {
+        return None;
+    }
+    let single_line = paragraph.children().all(|node| {
+        !matches!(
+            node.data.borrow().value,
+            NodeValue::SoftBreak | NodeValue::LineBreak
+        )
+    });
+    let mut text = String::new();
+    text_content(paragraph, &mut text);
+    let caption = text.strip_prefix(CAPTION_PREFIX)?.trim();
+    (single_line && !caption.is_empty()).then(|| caption.to_string())
+}
+
+/// Returns true if `node` is a paragraph without any visible content, like one made of nothing
+/// but `&nbsp;`.
+fn empty_paragraph<'a>(node: &'a AstNode<'a>) -> bool {
+    matches!(node.data.borrow().value, NodeValue::Paragraph)
+        && node
+            .descendants()
+            .skip(1)
+            .all(|node| match &node.data.borrow().value {
+                NodeValue::Text(text) => String::from_utf8_lossy(text).trim().is_empty(),
+                NodeValue::SoftBreak | NodeValue::LineBreak | NodeValue::HtmlInline(_) => true,
+                _ => false,
+            })
+}
+
+/// Matches list items like `` `--verbose`: enable logging `` documenting an option and returns
+/// the option.
+fn option_flag<'a>(item: &'a AstNode<'a>) -> Option<String> {
+    if !matches!(item.data.borrow().value, NodeValue::Item(_)) {
+        return None;
+    }
+    let paragraph = item.first_child()?;
+    if !matches!(paragraph.data.borrow().value, NodeValue::Paragraph) {
+        return None;
+    }
+    let code = paragraph.first_child()?;
+    let flag = match &code.data.borrow().value {
+        NodeValue::Code(code) => String::from_utf8_lossy(&code.literal).to_string(),
+        _ => return None,
+    };
+    match &code.next_sibling()?.data.borrow().value {
+        NodeValue::Text(text) if text.starts_with(b":") => Some(flag),
+        _ => None,
+    }
+}
+
+/// Returns true if the `code` span is the option an option list item starts with, it is rendered
+/// as the tag of the item.
+fn option_code<'a>(code: &'a AstNode<'a>) -> bool {
+    code.previous_sibling().is_none()
+        && code
+            .parent()
+            .filter(|paragraph| paragraph.previous_sibling().is_none())
+            .and_then(|paragraph| paragraph.parent())
+            .and_then(option_flag)
+            .is_some()
+}
+
+/// Longest line that is still recognized as the term of a definition.
+const MAX_TERM_LENGTH: usize = 40;
+
+/// Returns true if the `description` reads as the continuation of a sentence starting with the
+/// capitalized word `term`, like `Wrapping` followed by `lines is hard`.
+fn continues_sentence(term: &str, description: &str) -> bool {
+    let mut chars = term.chars();
+    let capitalized = chars.next().is_some_and(char::is_uppercase)
+        && chars.all(|c| c.is_alphabetic() && c.is_lowercase());
+    capitalized && description.starts_with(char::is_lowercase)
+}
+
+/// Matches top level paragraphs written as a term on a short line followed by its definition on
+/// an indented line or a line starting with `: ` and returns the term with its definition. Terms
+/// followed by an indented line can't contain spaces.
+fn definition<'a>(paragraph: &'a AstNode<'a>, source: &str) -> Option<(String, String)> {
+    let parent = paragraph.parent()?;
+    if !matches!(parent.data.borrow().value, NodeValue::Document) {
+        return None;
+    }
+    let line = paragraph.data.borrow().start_line as usize;
+    let next_line = source.lines().nth(line)?;
+
+    let mut term = String::new();
+    let mut next = paragraph.first_child();
+    while let Some(node) = next {
+        next = node.next_sibling();
+        match node.data.borrow().value {
+            NodeValue::SoftBreak => break,
+            NodeValue::LineBreak => return None,
+            _ => text_content(node, &mut term),
+        }
+    }
+    let term = term.trim();
+    if term.is_empty()
+        || term.chars().count() > MAX_TERM_LENGTH
+        || term.ends_with(['.', ',', ';', ':', '!', '?'])
+    {
+        return None;
+    }
+
+    let mut description = String::new();
+    while let Some(node) = next {
+        text_content(node, &mut description);
+        next = node.next_sibling();
+    }
+    let description = if next_line.starts_with([' ', '\t']) {
+        // an indented line may as well continue the sentence of a wrapped paragraph, only the
+        // names of flags or identifiers are taken as terms
+        let description = description.trim();
+        if term.contains(char::is_whitespace) || continues_sentence(term, description) {
+            return None;
+        }
+        description
+    } else {
+        description.strip_prefix(": ")?.trim()
+    };
+    if description.is_empty() {
+        return None;
+    }
+
+    Some((term.to_string(), description.to_string()))
+}
+
+/// The roff nodes of a single chapter.
+struct RenderedChapter {
+    body: Vec<RoffNode>,
+    /// Footnote definitions referenced in the body, numbered in the order of first reference.
+    notes: Vec<RoffNode>,
+    /// Tagged code blocks and their descriptions moved to the `EXAMPLES` section.
+    examples: Vec<RoffNode>,
+    /// Full addresses of the links shortened in the body, listed in the `SEE ALSO` section.
+    urls: Vec<String>,
+    /// Set if the chapter consists of nothing but code blocks.
+    code_only: bool,
+    /// The usage block promoted to the `SYNOPSIS` section of the page.
+    usage: Option<RoffNode>,
+    /// Text of the leading level 1 heading taken as the title of the chapter.
+    title: Option<String>,
+    /// Name and line of the first element without a roff representation.
+    unhandled: Option<(&'static str, usize)>,
+}
+
+impl RenderedChapter {
+    /// Fails with the first element of the chapter `name` without a roff representation if
+    /// `strict` is enabled.
+    fn check_strict(&self, name: &str, cfg: &ManOutputConfiguration) -> Result<(), Error> {
+        match self.unhandled {
+            Some((element, line)) if cfg.strict => Err(Error::Unhandled {
+                chapter: name.to_string(),
+                element: element.to_string(),
+                line,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the title of the section the chapter `ch` is rendered in.
+    fn section_title(
+        &self,
+        ctx: &RenderContext,
+        ch: &Chapter,
+        cfg: &ManOutputConfiguration,
+    ) -> String {
+        chapter_section_title(ctx, ch, self.title.as_deref(), cfg)
+    }
+
+    /// Returns the body with the notes appended under a `NOTES` heading.
+    fn into_nodes(self) -> Vec<RoffNode> {
+        let mut nodes = self.body;
+        if !self.notes.is_empty() {
+            nodes.extend(heading(["NOTES".roff().bold().into_roff()], 5));
+            nodes.extend(self.notes);
+        }
+        nodes
     }
 }
 
@@ -21,9 +619,172 @@ where
 struct Parser {
     nodes: Vec<RoffNode>,
     last_md_node: MarkdownNode,
+    /// Nodes of elements whose content is still being rendered, like headings.
+    groups: Vec<Vec<RoffNode>>,
+    /// Rows of the table being rendered.
+    table: Vec<table::Row>,
+    /// Footnote definitions in the order of their numbers.
+    notes: Vec<RoffNode>,
+    /// Font styles of all inline elements enclosing the current node.
+    styles: Vec<FontStyle>,
+    /// Set after a node that ends its line. Leading whitespace of the following text is dropped
+    /// as roff would otherwise break the line there.
+    trim_next: bool,
+    /// Set when parsing the synopsis chapter of the book.
+    synopsis: bool,
+    /// Nesting depth of the current node.
+    depth: usize,
+    /// Deepest nesting of nodes that is still rendered.
+    max_depth: usize,
+    /// Set if any nodes were skipped for being nested too deep.
+    truncated: bool,
+    /// Number of overstruck strikethrough elements enclosing the current node.
+    overstrike: usize,
+    /// Number of `<samp>` elements enclosing the current node, their text is set in a constant
+    /// width font.
+    constant_width: usize,
+    /// Keep whitespace at the edges of styled text inside the styled run.
+    keep_styled_whitespace: bool,
+    /// Term of the description whose details are being rendered.
+    term: Option<String>,
+    /// Name and line of the first element without a roff representation.
+    unhandled: Option<(&'static str, usize)>,
+    /// Abbreviations defined in the chapter, expanded on their first use.
+    abbreviations: Vec<Abbreviation>,
+    /// Inline HTML elements like `<kbd>` enclosing the current node.
+    elements: Vec<InlineElement>,
+    /// How the content of `<kbd>` elements is rendered.
+    kbd_style: KbdStyle,
+    /// Number of groups open when each of the enclosing `<details>` elements started.
+    details: Vec<usize>,
+    /// Length of the longest run of a word without a break point.
+    break_words: Option<usize>,
+    /// Rendered tagged code blocks of the chapter, see [`RenderedChapter::examples`].
+    examples: Vec<RoffNode>,
+    /// Length the addresses of links are shortened to.
+    truncate_urls: Option<usize>,
+    /// Addresses of the links that were shortened, in the order of their first use.
+    urls: Vec<String>,
 }
 
 impl Parser {
+    pub fn push_style(&mut self, style: FontStyle) {
+        self.styles.push(style);
+    }
+
+    pub fn pop_style(&mut self) {
+        self.styles.pop();
+    }
+
+    /// Appends the address of a link, after the display text of the link if it is `labelled`.
+    /// Addresses longer than `truncate_urls` are shortened and collected for the `SEE ALSO`
+    /// section.
+    pub fn append_url(&mut self, url: &str, labelled: bool) {
+        let shortened = self
+            .truncate_urls
+            .filter(|&len| url.chars().count() > len)
+            .map(|len| format!("{}...", url.chars().take(len).collect::<String>()));
+        let shown = shortened.as_deref().unwrap_or(url);
+        let shown = self
+            .break_words
+            .map_or(Cow::Borrowed(shown), |width| break_words(shown, width));
+        if shortened.is_none() {
+            self.append_roff(RoffNode::url("", shown.as_ref()));
+            self.trim_next = true;
+            return;
+        }
+        let shown = if labelled {
+            format!(" \\(la{}\\(ra", shown)
+        } else {
+            shown.into_owned()
+        };
+        self.append_roff(shown);
+        if !self.urls.iter().any(|other| other == url) {
+            self.urls.push(url.to_string());
+        }
+    }
+
+    /// Creates a text node with the combination of all currently open styles applied.
+    pub fn styled(&mut self, text: &str) -> RoffNode {
+        let text = if std::mem::take(&mut self.trim_next) {
+            text.trim_start()
+        } else {
+            text
+        };
+        let text = self
+            .break_words
+            .map_or(Cow::Borrowed(text), |width| break_words(text, width));
+        let text = text.as_ref();
+        let bold = self.styles.contains(&FontStyle::Bold);
+        let italic = self.styles.contains(&FontStyle::Italic);
+        let font = match (self.constant_width > 0, bold, italic) {
+            (true, true, true) => "\\f[CBI]",
+            (true, true, false) => "\\f(CB",
+            (true, false, true) => "\\f(CI",
+            (true, false, false) => "\\f(CR",
+            (false, true, true) => "\\f(BI",
+            (false, true, false) => "\\fB",
+            (false, false, true) => "\\fI",
+            (false, false, false) => return text.into_roff(),
+        };
+        if self.keep_styled_whitespace {
+            return RoffNode::text(format!("{}{}\\fR", font, text));
+        }
+        // spaces at the edges of a run would be underlined or emboldened along with it
+        let trimmed = text.trim_start();
+        let leading = &text[..text.len() - trimmed.len()];
+        let core = trimmed.trim_end();
+        let trailing = &trimmed[core.len()..];
+        if core.is_empty() {
+            return text.into_roff();
+        }
+        RoffNode::text(format!("{}{}{}\\fR{}", leading, font, core, trailing))
+    }
+
+    /// Creates a text node like [`styled`](Parser::styled) keeping the whitespace at its edges
+    /// inside the styled run, for text that has to render exactly like code.
+    pub fn styled_verbatim(&mut self, text: &str) -> RoffNode {
+        let keep = std::mem::replace(&mut self.keep_styled_whitespace, true);
+        let node = self.styled(text);
+        self.keep_styled_whitespace = keep;
+        node
+    }
+
+    /// Starts or ends the inline HTML `element`, ending it only if it is the innermost open one.
+    pub fn toggle_element(&mut self, element: InlineElement, open: bool) {
+        if !open && self.elements.last() != Some(&element) {
+            // a closing tag without an opening one has nothing to end
+            return;
+        }
+        match element {
+            InlineElement::Kbd => match self.kbd_style {
+                KbdStyle::Bold if open => self.push_style(FontStyle::Bold),
+                KbdStyle::Bold => self.pop_style(),
+                KbdStyle::Brackets => {
+                    let text = self.styled(if open { "[" } else { "]" });
+                    self.append_roff(text);
+                }
+            },
+            InlineElement::Var if open => self.push_style(FontStyle::Italic),
+            InlineElement::Var => self.pop_style(),
+            InlineElement::Samp if open => self.constant_width += 1,
+            InlineElement::Samp => self.constant_width -= 1,
+            InlineElement::Break => return,
+        }
+        if open {
+            self.elements.push(element);
+        } else {
+            self.elements.pop();
+        }
+    }
+
+    /// Ends the inline HTML elements left open at the end of the block they were opened in.
+    pub fn close_elements(&mut self) {
+        while let Some(&element) = self.elements.last() {
+            self.toggle_element(element, false);
+        }
+    }
+
     pub fn update_last_node(&mut self, node: MarkdownNode) {
         self.last_md_node = node;
     }
@@ -32,32 +793,160 @@ impl Parser {
         &self.last_md_node
     }
 
-    pub fn finalize(self) -> Vec<RoffNode> {
-        self.nodes
+    /// Follows the first use of every abbreviation in `text` with its expansion in parentheses.
+    pub fn expand_abbreviations<'t>(&mut self, text: Cow<'t, str>) -> Cow<'t, str> {
+        let mut text = text;
+        for abbreviation in self.abbreviations.iter_mut().filter(|a| !a.used) {
+            if let Some(i) = find_word(&text, &abbreviation.name) {
+                let end = i + abbreviation.name.len();
+                let expanded = format!(
+                    "{} ({}){}",
+                    &text[..end],
+                    abbreviation.expansion,
+                    &text[end..]
+                );
+                text = Cow::Owned(expanded);
+                abbreviation.used = true;
+            }
+        }
+        text
+    }
+
+    pub fn finalize(self) -> RenderedChapter {
+        RenderedChapter {
+            body: self.nodes,
+            notes: self.notes,
+            examples: self.examples,
+            urls: self.urls,
+            code_only: false,
+            usage: None,
+            title: None,
+            unhandled: self.unhandled,
+        }
+    }
+
+    pub fn append_note(&mut self, note: RoffNode) {
+        self.notes.push(note);
     }
 
     pub fn append_roff(&mut self, roff: impl IntoRoffNode) {
-        self.nodes.push(roff.into_roff());
+        match self.groups.last_mut() {
+            Some(group) => group.push(roff.into_roff()),
+            None => self.nodes.push(roff.into_roff()),
+        }
+    }
+
+    /// Starts collecting all appended nodes into a separate group until [`end_group`](Parser::end_group)
+    /// is called.
+    pub fn begin_group(&mut self) {
+        self.groups.push(vec![]);
+    }
+
+    /// Returns the nodes appended since the matching [`begin_group`](Parser::begin_group).
+    pub fn end_group(&mut self) -> Vec<RoffNode> {
+        self.groups.pop().unwrap_or_default()
+    }
+
+    /// Starts collecting the content of a `<details>` element that is indented below its summary.
+    pub fn open_details(&mut self) {
+        self.begin_group();
+        self.details.push(self.groups.len());
+    }
+
+    /// Appends the `summary` of the innermost `<details>` element as a bold label. The label is
+    /// placed above the indented content unless some of the content was already rendered.
+    pub fn label_details(&mut self, summary: &str) {
+        let label = RoffNode::paragraph([summary.roff().bold()]);
+        let empty = self.details.last() == Some(&self.groups.len())
+            && self.groups.last().is_some_and(Vec::is_empty);
+        if empty {
+            self.groups.pop();
+            self.append_roff(label);
+            self.groups.push(vec![]);
+        } else {
+            self.append_roff(label);
+        }
+    }
+
+    /// Ends the innermost `<details>` element, unless it was opened as part of another element
+    /// that is still being rendered. Returns whether an element was closed.
+    pub fn close_details(&mut self) -> bool {
+        if self.details.last() != Some(&self.groups.len()) {
+            return false;
+        }
+        self.details.pop();
+        let content = self.end_group();
+        self.append_roff(RoffNode::nested(content));
+        true
     }
 }
 
-#[derive(Copy, Debug, Clone)]
-enum MarkdownNode {
+/// Kind of a markdown element, the elements this renderer handles differently from each other
+/// with the details of comrak's [`NodeValue`](NodeValue) left out. Elements of any other kind
+/// are [`Empty`](MarkdownNode::Empty). More kinds are added as the renderer supports more
+/// elements.
+#[derive(Copy, Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MarkdownNode {
+    /// Heading of any level.
     Heading,
+    /// Paragraph of text.
     Paragraph,
+    /// Inline code span.
     Code,
+    /// Indented or fenced code block.
     CodeBlock,
+    /// Bold text.
     Strong,
+    /// Italic text.
     Emphasis,
+    /// Link with its display text.
     Link,
+    /// Line break within a paragraph filled like a space.
     SoftBreak,
+    /// Plain text.
     Text,
+    /// Ordered or bullet list.
     List,
+    /// Item of a list.
     ListItem,
+    /// Hard line break.
     LineBreak,
+    /// Image with its alternative text.
     Image,
+    /// Block quote.
+    BlockQuote,
+    /// Table with its rows.
+    Table,
+    /// Row of a table, the header included.
+    TableRow,
+    /// Cell of a table row.
+    TableCell,
+    /// Thematic break like `---`.
+    ThematicBreak,
+    /// Block of raw HTML.
+    HtmlBlock,
+    /// Inline raw HTML.
+    HtmlInline,
+    /// Struck through text.
+    Strikethrough,
+    /// Superscript text.
+    Superscript,
+    /// Marker of a checked or unchecked task list item.
+    TaskItem,
+    /// Reference to a footnote.
+    FootnoteReference,
+    /// Definition of a footnote.
+    FootnoteDefinition,
+    /// List of terms and their details.
+    DescriptionList,
+    /// Term of a description list.
+    DescriptionTerm,
+    /// Details of a term of a description list.
+    DescriptionDetails,
 
-    // fallback
+    /// Any other element.
+    #[default]
     Empty,
 }
 
@@ -77,118 +966,2006 @@ impl From<&NodeValue> for MarkdownNode {
             NodeValue::Item(_) => MarkdownNode::ListItem,
             NodeValue::LineBreak => MarkdownNode::LineBreak,
             NodeValue::Image(_) => MarkdownNode::Image,
+            NodeValue::BlockQuote => MarkdownNode::BlockQuote,
+            NodeValue::Table(_) => MarkdownNode::Table,
+            NodeValue::TableRow(_) => MarkdownNode::TableRow,
+            NodeValue::TableCell => MarkdownNode::TableCell,
+            NodeValue::ThematicBreak => MarkdownNode::ThematicBreak,
+            NodeValue::HtmlBlock(_) => MarkdownNode::HtmlBlock,
+            NodeValue::HtmlInline(_) => MarkdownNode::HtmlInline,
+            NodeValue::Strikethrough => MarkdownNode::Strikethrough,
+            NodeValue::Superscript => MarkdownNode::Superscript,
+            NodeValue::TaskItem(_) => MarkdownNode::TaskItem,
+            NodeValue::FootnoteReference(_) => MarkdownNode::FootnoteReference,
+            NodeValue::FootnoteDefinition(_) => MarkdownNode::FootnoteDefinition,
+            NodeValue::DescriptionList => MarkdownNode::DescriptionList,
+            NodeValue::DescriptionTerm => MarkdownNode::DescriptionTerm,
+            NodeValue::DescriptionDetails => MarkdownNode::DescriptionDetails,
             _ => MarkdownNode::Empty,
         }
     }
 }
 
-impl Default for MarkdownNode {
-    fn default() -> Self {
-        MarkdownNode::Empty
-    }
+/// Attributes used by rustdoc and mdBook in code block info strings that are not a language.
+const CODE_BLOCK_ATTRIBUTES: &[&str] = &[
+    "ignore",
+    "no_run",
+    "should_panic",
+    "compile_fail",
+    "editable",
+    "noplayground",
+    "noplaypen",
+    "mdbook-runnable",
+    "numberLines",
+];
+
+/// Splits a code block info string like `rust`, `rust,no_run` or `{.rust .numberLines}` into
+/// its words.
+fn info_tokens(info: &str) -> impl Iterator<Item = &str> {
+    let info = info.trim();
+    let info = info
+        .strip_prefix('{')
+        .and_then(|info| info.strip_suffix('}'))
+        .unwrap_or(info);
+
+    info.split(|c: char| c == ',' || c.is_whitespace())
+        .map(|token| token.trim_start_matches('.'))
+        .filter(|token| !token.is_empty())
 }
 
-fn markdown_to_roff<'a>(text: &'a str, arena: &'a Arena<AstNode<'a>>) -> Vec<RoffNode> {
-    let mut parser = Parser::default();
-    let root = parse_document(arena, text, &ComrakOptions::default());
+/// Extracts a clean language name from a code block info string like `rust`, `rust,no_run` or
+/// `{.rust .numberLines}`.
+fn code_block_language(info: &str) -> Option<&str> {
+    info_tokens(info).find(|token| {
+        !token.contains('=')
+            && !token.starts_with('#')
+            && !token.starts_with("edition")
+            && !CODE_BLOCK_ATTRIBUTES.contains(token)
+    })
+}
 
-    iter_nodes(root, &mut parser, &|node, parser| {
-        let value = &node.data.borrow().value;
-        match value {
-            NodeValue::Link(ref link) | NodeValue::Image(ref link) => {
-                let url = String::from_utf8_lossy(link.url.as_slice());
-                let title = String::from_utf8_lossy(link.title.as_slice());
-                parser.append_roff(RoffNode::url(title, url));
-            }
-            NodeValue::Code(code) => {
-                let text = String::from_utf8_lossy(code.literal.as_slice());
-                parser.append_roff("`".into_roff());
-                parser.append_roff(text.roff().italic().into_roff());
-                parser.append_roff("`".into_roff());
-            }
-            NodeValue::CodeBlock(ref block) => {
-                let text = String::from_utf8_lossy(block.literal.as_slice());
-                let info = String::from_utf8_lossy(block.info.as_slice());
-                let title = if !info.is_empty() {
-                    Some(info.roff().bold())
-                } else {
-                    None
-                };
-                let para = RoffNode::nested([RoffNode::indented_paragraph(
-                    [RoffNode::linebreak(), RoffNode::example([text.as_ref()])],
-                    Some(2),
-                    title,
-                )]);
-                parser.append_roff(para);
-            }
-            NodeValue::Text(ref text) => {
-                let text = String::from_utf8_lossy(text);
-                match parser.last_node() {
-                    MarkdownNode::Heading => {
-                        parser.append_roff(RoffNode::linebreak());
-                        parser.append_roff(RoffNode::linebreak());
-                        parser.append_roff(text.roff().bold().into_roff());
-                        parser.append_roff(RoffNode::linebreak());
-                        parser.append_roff("=".repeat(text.len() + 2).into_roff());
-                        parser.append_roff(RoffNode::linebreak());
-                        return;
-                    }
+/// Marks the end of a wrapped part of a code line until the text is escaped, one of the
+/// [`raw::MARKERS`](raw::MARKERS) that never occur in the source.
+const WRAP_MARKER: char = '\u{FDD3}';
 
-                    MarkdownNode::Paragraph => {
-                        parser.append_roff(RoffNode::paragraph([text]));
-                    }
-                    MarkdownNode::Emphasis => {
-                        parser.append_roff(text.roff().italic().into_roff());
-                    }
-                    MarkdownNode::Strong => {
-                        parser.append_roff(text.roff().bold().into_roff());
-                    }
-                    MarkdownNode::ListItem => {
-                        parser.append_roff(text.into_roff());
-                        parser.append_roff(RoffNode::linebreak());
-                    }
-                    _ => {
-                        parser.append_roff(text.into_roff());
-                    }
-                }
+/// Glyph ending the wrapped parts of code lines, an arrow that can't be mistaken for a
+/// backslash ending a line of the code itself.
+const WRAP_GLYPH: &str = "\\(rA";
+
+/// Wraps lines of `text` longer than `width` columns, ending each wrapped part with a
+/// [`WRAP_MARKER`](WRAP_MARKER).
+fn wrap_code(text: &str, width: usize) -> String {
+    let width = width.max(2);
+    let mut out = String::with_capacity(text.len());
+    for line in text.split_inclusive('\n') {
+        let (line, newline) = match line.strip_suffix('\n') {
+            Some(line) => (line, "\n"),
+            None => (line, ""),
+        };
+        let mut chars: Vec<char> = line.chars().collect();
+        while chars.len() > width {
+            let rest = chars.split_off(width - 1);
+            out.extend(chars);
+            out.push(WRAP_MARKER);
+            out.push('\n');
+            chars = rest;
+        }
+        out.extend(chars);
+        out.push_str(newline);
+    }
+    out
+}
+
+/// Renders both fenced and indented code blocks as an indented example. Only fenced blocks with
+/// a recognizable language get a title.
+fn code_block(block: &NodeCodeBlock, cfg: &ManOutputConfiguration) -> RoffNode {
+    let text = String::from_utf8_lossy(block.literal.as_slice());
+    let text = match cfg.code_wrap_width {
+        Some(width) => escape::code(&wrap_code(&text, width)).replace(WRAP_MARKER, WRAP_GLYPH),
+        None => escape::code(&text),
+    };
+    let info = String::from_utf8_lossy(block.info.as_slice());
+    let title = code_block_language(&info)
+        .filter(|lang| block.fenced && !(cfg.math && *lang == math::LANGUAGE))
+        .and_then(|lang| {
+            let title = format!(
+                "{}{}",
+                cfg.code_title_prefix.as_deref().unwrap_or_default(),
+                lang
+            );
+            match cfg.code_title_style {
+                CodeTitleStyle::Bold => Some(title.roff().bold()),
+                CodeTitleStyle::Italic => Some(title.roff().italic()),
+                CodeTitleStyle::Roman => Some(title.roff()),
+                CodeTitleStyle::None => None,
             }
-            NodeValue::LineBreak => {
-                parser.append_roff(RoffNode::linebreak());
+        });
+    let content = match cfg.code_block_style {
+        CodeBlockStyle::Example => vec![RoffNode::linebreak(), RoffNode::example([text.as_str()])],
+        CodeBlockStyle::Verbatim => vec![
+            RoffNode::linebreak(),
+            raw::request(".nf"),
+            RoffNode::text(text.as_str()),
+            raw::request(".fi"),
+        ],
+    };
+    RoffNode::nested([RoffNode::indented_paragraph(content, Some(2), title)])
+}
+
+/// Renders a single argument of a usage line. Flags are bold, other arguments are italic while
+/// the surrounding brackets and ellipsis stay in roman.
+fn synopsis_argument(arg: &str, out: &mut Vec<RoffNode>) {
+    let core = arg.trim_start_matches(['[', '(', '<']);
+    let leading = &arg[..arg.len() - core.len()];
+    let trimmed = core.trim_end_matches([']', ')', '>', '.', '|', ',']);
+    let trailing = &core[trimmed.len()..];
+
+    let leading = leading.replace('<', "");
+    let trailing = trailing.replace('>', "");
+    if !leading.is_empty() {
+        out.push(leading.into_roff());
+    }
+    if trimmed.starts_with('-') {
+        out.push(trimmed.roff().bold().into_roff());
+    } else if !trimmed.is_empty() {
+        out.push(trimmed.roff().italic().into_roff());
+    }
+    if !trailing.is_empty() {
+        out.push(trailing.into_roff());
+    }
+}
+
+/// Renders the usage lines in the code `block` of the synopsis chapter with the command in bold
+/// and its arguments in italic.
+fn synopsis_block(block: &NodeCodeBlock) -> RoffNode {
+    let text = String::from_utf8_lossy(block.literal.as_slice());
+    let mut nodes = vec![];
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        let line = line.strip_prefix("$ ").unwrap_or(line);
+        let mut words = line.split_whitespace();
+        if let Some(command) = words.next() {
+            if !nodes.is_empty() {
+                nodes.push(RoffNode::linebreak());
             }
-            _ => {}
+            nodes.push(command.roff().bold().into_roff());
         }
+        for word in words {
+            nodes.push(" ".into_roff());
+            synopsis_argument(word, &mut nodes);
+        }
+    }
+    RoffNode::paragraph(nodes)
+}
 
-        parser.update_last_node(MarkdownNode::from(value));
-    });
-    parser.finalize()
+/// Returns the title of the section the chapter `name` is rendered in.
+fn section_title(ctx: &RenderContext, name: &str, cfg: &ManOutputConfiguration) -> String {
+    if cfg.is_synopsis_chapter(name) {
+        return "SYNOPSIS".into();
+    }
+
+    let prefix = cfg
+        .section_prefix
+        .as_deref()
+        .or(ctx.config.book.title.as_deref())
+        .unwrap_or_default();
+    if !cfg.prefix_sections || prefix.is_empty() || name.starts_with(prefix) {
+        name.into()
+    } else {
+        format!("{} {}", prefix, name)
+    }
 }
 
-pub fn mdbook_to_roff(ctx: &RenderContext) -> Roff {
-    let arena = Arena::new();
-    let title = ctx.config.book.title.as_deref().unwrap_or_default();
-    let mut page = Roff::new(&title, SectionNumber::Miscellaneous);
+/// Returns the title of the section the chapter `ch` is rendered in, the `title` taken from its
+/// leading heading or else its name.
+fn chapter_section_title(
+    ctx: &RenderContext,
+    ch: &Chapter,
+    title: Option<&str>,
+    cfg: &ManOutputConfiguration,
+) -> String {
+    let title = match title {
+        Some(title) if !cfg.is_synopsis_chapter(&ch.name) => section_title(ctx, title, cfg),
+        _ => section_title(ctx, &ch.name, cfg),
+    };
+    numbered_title(ch, title, cfg)
+}
+
+/// A section of the combined page holding a chapter.
+struct ChapterSection {
+    /// Title of the section, told apart from earlier sections with the same title as configured
+    /// with `duplicate_sections`.
+    title: String,
+    /// Set if the chapter is appended to an earlier section with the same title.
+    merged: bool,
+}
+
+/// Returns the sections of the combined page the `chapters` are rendered in, in their order.
+fn chapter_sections(
+    ctx: &RenderContext,
+    chapters: &[&Chapter],
+    cfg: &ManOutputConfiguration,
+) -> Vec<ChapterSection> {
+    let mut titles: Vec<String> = Vec::with_capacity(chapters.len());
+    let mut sections = Vec::with_capacity(chapters.len());
+    for (i, ch) in chapters.iter().enumerate() {
+        let role = ChapterRole::of(chapters, i, cfg);
+        let heading = chapter_title(&ch.content, cfg, role);
+        let title = chapter_section_title(ctx, ch, heading.as_deref(), cfg);
+
+        let count = titles.iter().filter(|other| **other == title).count();
+        let part = part_title(&ctx.book, ch).filter(|part| {
+            // the part only tells the sections apart if the other chapters are in other parts
+            !chapters.iter().zip(&titles).any(|(other, other_title)| {
+                *other_title == title && part_title(&ctx.book, other) == Some(part)
+            })
+        });
+        titles.push(title.clone());
+        let section = match (count, cfg.duplicate_sections, part) {
+            (0, ..) => ChapterSection {
+                title,
+                merged: false,
+            },
+            (_, DuplicateSections::Merge, _) => ChapterSection {
+                title,
+                merged: true,
+            },
+            (_, DuplicateSections::Part, Some(part)) => ChapterSection {
+                title: format!("{} ({})", title, part),
+                merged: false,
+            },
+            _ => ChapterSection {
+                title: format!("{} ({})", title, count + 1),
+                merged: false,
+            },
+        };
+        sections.push(section);
+    }
+    sections
+}
 
-    for item in ctx.book.iter() {
-        if let BookItem::Chapter(ref ch) = *item {
-            let parsed = markdown_to_roff(ch.content.as_str(), &arena);
-            page = page.section(ch.name.as_str(), parsed);
+/// Puts the number of the chapter `ch` in front of its section `title` if `section_numbers` is
+/// enabled. Prefix and suffix chapters have no number and keep their title as it is.
+fn numbered_title(ch: &Chapter, title: String, cfg: &ManOutputConfiguration) -> String {
+    match &ch.number {
+        Some(number) if cfg.section_numbers && !cfg.is_synopsis_chapter(&ch.name) => {
+            format!("{} {}", number, title)
         }
+        _ => title,
     }
+}
 
-    page
+/// Lines kept on the same page as an in-body heading when `keep_together` is enabled, the
+/// heading itself, its underline and two lines of the following text.
+const HEADING_NEED_LINES: usize = 5;
+
+/// Longest examples kept on a single page when `keep_together` is enabled.
+const MAX_KEPT_EXAMPLE_LINES: usize = 20;
+
+/// Renders a heading with `content` as a line underlined to match the `len` of its text.
+fn heading(content: impl IntoIterator<Item = RoffNode>, len: usize) -> Vec<RoffNode> {
+    let mut nodes = vec![RoffNode::linebreak(), RoffNode::linebreak()];
+    nodes.extend(content);
+    nodes.extend([
+        RoffNode::linebreak(),
+        "=".repeat(len + 2).into_roff(),
+        RoffNode::linebreak(),
+    ]);
+    nodes
 }
 
-pub fn mdbook_to_roff_chapters(ctx: &RenderContext) -> Vec<Roff> {
-    let arena = Arena::new();
-    let mut pages = vec![];
-    for item in ctx.book.iter() {
-        if let BookItem::Chapter(ref ch) = *item {
-            let mut page = Roff::new(ch.name.as_str(), SectionNumber::Miscellaneous);
-            let parsed = markdown_to_roff(ch.content.as_str(), &arena);
-            page = page.section(ch.name.as_str(), parsed);
-            pages.push(page);
+/// Returns the line of the source `node` starts on. Inline elements have no position of their
+/// own, the line of the block they are part of is used instead.
+fn source_line<'a>(node: &'a AstNode<'a>) -> usize {
+    node.ancestors()
+        .map(|node| node.data.borrow().start_line as usize)
+        .find(|line| *line > 0)
+        .unwrap_or(1)
+}
+
+/// Renders a markdown element on `line` that has no roff representation according to the
+/// configured fallback.
+fn unhandled_node(
+    value: &NodeValue,
+    line: usize,
+    parser: &mut Parser,
+    cfg: &ManOutputConfiguration,
+) {
+    let (name, text) = match value {
+        NodeValue::HtmlBlock(block) => ("html block", Some(block.literal.as_slice())),
+        NodeValue::HtmlInline(html) => ("inline html", Some(html.as_slice())),
+        NodeValue::FrontMatter(text) => ("front matter", Some(text.as_slice())),
+        NodeValue::ThematicBreak => ("thematic break", None),
+        NodeValue::BlockQuote => ("block quote", None),
+        _ => ("element", None),
+    };
+    parser.unhandled.get_or_insert((name, line));
+    match cfg.unhandled_nodes {
+        UnhandledNodes::Drop => {}
+        UnhandledNodes::Text => {
+            if let Some(text) = text {
+                let text = String::from_utf8_lossy(text);
+                let text = parser.styled(text.trim_end());
+                if value.block() {
+                    parser.append_roff(RoffNode::paragraph([text]));
+                } else {
+                    parser.append_roff(text);
+                }
+            }
+        }
+        UnhandledNodes::Comment => {
+            parser.append_roff(raw::request(format!(".\\\" unhandled {}", name)));
+            parser.trim_next = true;
+        }
+    }
+}
+
+/// Renders an HTML block kept with `unsafe_html`, comments become roff comments while other
+/// markup is kept as written.
+fn raw_html_block(html: &str) -> RoffNode {
+    let html = html.trim_end();
+    match html
+        .strip_prefix("<!--")
+        .and_then(|comment| comment.strip_suffix("-->"))
+    {
+        Some(comment) => {
+            let lines: Vec<_> = comment
+                .trim()
+                .lines()
+                .map(|line| format!(".\\\" {}", line.trim_end()))
+                .collect();
+            raw::request(lines.join("\n"))
         }
+        None => RoffNode::paragraph([RoffNode::text(escape::code(html))]),
     }
+}
+
+/// Inline HTML element rendered according to its meaning instead of as unhandled markup.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum InlineElement {
+    /// `<kbd>`, a key styled according to `kbd_style`.
+    Kbd,
+    /// `<samp>`, sample output in a constant width roman font.
+    Samp,
+    /// `<var>`, a variable in italics.
+    Var,
+    /// `<br>`, a line break.
+    Break,
+}
+
+/// Names of the inline HTML elements with a meaning of their own.
+const INLINE_ELEMENTS: &[(&str, InlineElement)] = &[
+    ("kbd", InlineElement::Kbd),
+    ("samp", InlineElement::Samp),
+    ("var", InlineElement::Var),
+    ("br", InlineElement::Break),
+];
+
+/// Returns the element of the inline `html` tag and whether it opens the element, `None` for any
+/// other markup. Other elements are left to the unhandled fallback, which drops their tags but
+/// keeps their content by default.
+fn inline_element(html: &[u8]) -> Option<(InlineElement, bool)> {
+    let tag = String::from_utf8_lossy(html).trim().to_ascii_lowercase();
+    let tag = tag.strip_prefix('<')?.strip_suffix('>')?;
+    let (tag, open) = match tag.strip_prefix('/') {
+        Some(tag) => (tag, false),
+        None => (tag.trim_end_matches('/'), true),
+    };
+    let name = tag.split_whitespace().next()?;
+    INLINE_ELEMENTS
+        .iter()
+        .find(|(element, _)| *element == name)
+        .map(|(_, element)| (*element, open))
+}
+
+/// Tags and content of an HTML block that is part of a `<details>` element.
+struct DetailsBlock {
+    open: bool,
+    summary: Option<String>,
+    text: String,
+    close: bool,
+}
+
+/// Removes all HTML tags from `html`, leaving their content.
+fn strip_tags(html: &str) -> String {
+    let mut text = String::new();
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        rest = match rest[start..].find('>') {
+            Some(end) => &rest[start + end + 1..],
+            None => "",
+        };
+    }
+    text.push_str(rest);
+    text
+}
+
+/// Splits an HTML block into the `<details>` and `<summary>` tags it contains and the remaining
+/// text, returns `None` for blocks that are no part of a `<details>` element.
+fn details_block(html: &str) -> Option<DetailsBlock> {
+    // ASCII lowercasing keeps the byte offsets of the original
+    let lower = html.to_ascii_lowercase();
+    let open = lower.contains("<details");
+    let close = lower.contains("</details>");
+    let mut rest = html.to_string();
+    let mut summary = None;
+    if let Some(start) = lower.find("<summary") {
+        let content = lower[start..].find('>').map(|end| start + end + 1);
+        let end = lower.find("</summary>");
+        if let (Some(content), Some(end)) = (content, end.filter(|end| *end >= start)) {
+            summary = Some(strip_tags(&html[content..end]).trim().to_string());
+            rest = format!("{}{}", &html[..start], &html[end + "</summary>".len()..]);
+        }
+    }
+    if !open && !close && summary.is_none() {
+        return None;
+    }
+    Some(DetailsBlock {
+        open,
+        summary,
+        text: strip_tags(&rest).trim().to_string(),
+        close,
+    })
+}
+
+/// Nesting depth of markdown elements rendered if `max-depth` is not configured.
+const DEFAULT_MAX_DEPTH: usize = 128;
+
+/// Overstrikes every character of `text` with a slash.
+fn overstrike(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            c if c.is_whitespace() => c.to_string(),
+            '\\' => "\\o'\\e\\(sl'".to_string(),
+            '\'' => "\\o'\\(aq\\(sl'".to_string(),
+            c => format!("\\o'{}\\(sl'", c),
+        })
+        .collect()
+}
+
+/// Builds the options used to parse the markdown of every chapter.
+fn build_comrak_options(cfg: &ManOutputConfiguration) -> ComrakOptions {
+    let mut options = ComrakOptions::default();
+    let gfm = |extension: Option<bool>, default| extension.or(cfg.gfm).unwrap_or(default);
+    options.extension.table = gfm(cfg.tables, true);
+    options.extension.strikethrough = gfm(cfg.strikethrough, true);
+    options.extension.tasklist = gfm(cfg.tasklist, true);
+    options.extension.autolink = gfm(cfg.autolink, false);
+    options.extension.tagfilter = cfg.gfm.unwrap_or(false);
+    options.extension.footnotes = cfg.footnotes.unwrap_or(true);
+    options.extension.description_lists = cfg.description_lists.unwrap_or(false);
+    options.extension.superscript = cfg.superscript.unwrap_or(false);
+    options.render.unsafe_ = cfg.unsafe_html;
+    options
+}
+
+/// What a chapter is rendered as.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum ChapterRole {
+    /// A regular chapter.
+    Body,
+    /// The chapter rendered as the `SYNOPSIS` section.
+    Synopsis,
+    /// The first chapter of a book without a synopsis chapter, its usage block is promoted to
+    /// the `SYNOPSIS` section.
+    Intro,
+}
+
+impl ChapterRole {
+    fn of(chapters: &[&Chapter], index: usize, cfg: &ManOutputConfiguration) -> Self {
+        if cfg.is_synopsis_chapter(&chapters[index].name) {
+            ChapterRole::Synopsis
+        } else if index == 0
+            && cfg.synopsis_from_usage
+            && !chapters.iter().any(|ch| cfg.is_synopsis_chapter(&ch.name))
+        {
+            ChapterRole::Intro
+        } else {
+            ChapterRole::Body
+        }
+    }
+}
+
+/// Languages of code blocks that show how a command is used.
+const USAGE_LANGUAGES: &[&str] = &["console", "sh", "shell", "bash", "text"];
+
+/// Returns the first code block of the chapter if it shows the usage of a command and only
+/// paragraphs and headings of the introduction come before it.
+fn usage_block<'a>(root: &'a AstNode<'a>) -> Option<&'a AstNode<'a>> {
+    for node in root.children() {
+        match &node.data.borrow().value {
+            NodeValue::Paragraph | NodeValue::Heading(_) => {}
+            NodeValue::CodeBlock(block) if block.fenced => {
+                let info = String::from_utf8_lossy(&block.info);
+                let language = code_block_language(&info)?;
+                return USAGE_LANGUAGES.contains(&language).then_some(node);
+            }
+            _ => return None,
+        }
+    }
+    None
+}
+
+/// Moves the fenced code blocks tagged with `tag` to a document of their own, each with the
+/// paragraph right before it describing the example. The tag is removed from the info string so
+/// that it isn't taken for the language of the block.
+fn example_blocks<'a>(
+    root: &'a AstNode<'a>,
+    arena: &'a Arena<AstNode<'a>>,
+    tag: &str,
+) -> &'a AstNode<'a> {
+    let examples = arena.alloc(AstNode::new(RefCell::new(Ast::new(NodeValue::Document))));
+    let blocks: Vec<_> = root
+        .descendants()
+        .filter(|node| match &mut node.data.borrow_mut().value {
+            NodeValue::CodeBlock(block) if block.fenced => {
+                let info = String::from_utf8_lossy(&block.info).into_owned();
+                if !info_tokens(&info).any(|token| token == tag) {
+                    return false;
+                }
+                let rest: Vec<_> = info_tokens(&info).filter(|token| *token != tag).collect();
+                block.info = rest.join(" ").into_bytes();
+                true
+            }
+            _ => false,
+        })
+        .collect();
+    for block in blocks {
+        let description = block
+            .previous_sibling()
+            .filter(|node| matches!(node.data.borrow().value, NodeValue::Paragraph));
+        if let Some(description) = description {
+            examples.append(description);
+        }
+        examples.append(block);
+    }
+    examples
+}
+
+/// Dashes starting the attribution line of a block quote.
+const ATTRIBUTION_DASHES: &[&str] = &["\u{2014}", "--"];
+
+/// Turns the last line of every block quote that starts with an em dash or `--`, like
+/// `\u{2014} Author`, into an attribution on a line of its own in italics. The line is only taken
+/// as an attribution if the quote has any other text.
+fn quote_attributions<'a>(root: &'a AstNode<'a>, arena: &'a Arena<AstNode<'a>>) {
+    let quotes: Vec<_> = root
+        .descendants()
+        .filter(|node| matches!(node.data.borrow().value, NodeValue::BlockQuote))
+        .collect();
+    for quote in quotes {
+        let paragraph = match quote.last_child() {
+            Some(node) if matches!(node.data.borrow().value, NodeValue::Paragraph) => node,
+            _ => continue,
+        };
+        let line_break = paragraph.children().filter(|node| {
+            matches!(
+                node.data.borrow().value,
+                NodeValue::SoftBreak | NodeValue::LineBreak
+            )
+        });
+        let (line_break, start) = match line_break.last() {
+            Some(node) => (Some(node), node.next_sibling()),
+            None if paragraph.previous_sibling().is_some() => (None, paragraph.first_child()),
+            None => continue,
+        };
+        let start = match start {
+            Some(node) => node,
+            None => continue,
+        };
+        match &mut start.data.borrow_mut().value {
+            NodeValue::Text(text) => match ATTRIBUTION_DASHES
+                .iter()
+                .find(|d| text.starts_with(d.as_bytes()))
+            {
+                Some(dash) => {
+                    text.splice(..dash.len(), "\u{2014}".bytes());
+                }
+                None => continue,
+            },
+            _ => continue,
+        }
+
+        if let Some(line_break) = line_break {
+            line_break.data.borrow_mut().value = NodeValue::LineBreak;
+        }
+        let emphasis = arena.alloc(AstNode::new(RefCell::new(Ast::new(NodeValue::Emph))));
+        start.insert_before(emphasis);
+        let mut next = Some(start);
+        while let Some(node) = next {
+            next = node.next_sibling();
+            emphasis.append(node);
+        }
+    }
+}
+
+/// Prepares the markdown `text` of a chapter for parsing, returns the abbreviations defined in it
+/// with the text left to parse.
+fn preprocess<'t>(
+    text: &'t str,
+    cfg: &ManOutputConfiguration,
+) -> (Vec<Abbreviation>, Cow<'t, str>) {
+    let text = raw::sanitize(frontmatter::strip(text));
+    let (abbreviations, text) = if cfg.abbreviations {
+        let (abbreviations, text) = abbreviations(&text);
+        (abbreviations, Cow::Owned(text))
+    } else {
+        (vec![], text)
+    };
+    let text = if cfg.math {
+        Cow::Owned(math::preprocess(&text, &build_comrak_options(cfg)))
+    } else {
+        text
+    };
+    (abbreviations, text)
+}
+
+/// Detaches the level 1 heading starting the document `root` with `heading_as_title` and
+/// returns its content as the title of the chapter.
+fn leading_title<'a>(root: &'a AstNode<'a>, cfg: &ManOutputConfiguration) -> Option<String> {
+    root.first_child()
+        .filter(|node| match &node.data.borrow().value {
+            NodeValue::Heading(heading) => cfg.heading_as_title && heading.level == 1,
+            _ => false,
+        })
+        .map(|heading| {
+            heading.detach();
+            let mut text = String::new();
+            title_content(heading, cfg, &mut text);
+            text.trim().to_string()
+        })
+        .filter(|title| !title.is_empty())
+}
+
+/// Returns the title the chapter with the markdown `text` takes from its leading heading, see
+/// [`RenderedChapter::title`], without rendering the chapter.
+fn chapter_title(text: &str, cfg: &ManOutputConfiguration, role: ChapterRole) -> Option<String> {
+    if !cfg.heading_as_title {
+        return None;
+    }
+    let arena = Arena::new();
+    let text = preprocess(text, cfg).1;
+    let root = parse_document(&arena, &text, &build_comrak_options(cfg));
+    // the blocks moved out of the chapter before, as in `markdown_to_roff`
+    if cfg.examples {
+        example_blocks(root, &arena, cfg.example_tag());
+    }
+    if role == ChapterRole::Intro {
+        if let Some(usage) = usage_block(root) {
+            usage.detach();
+        }
+    }
+    leading_title(root, cfg)
+}
+
+fn markdown_to_roff<'a>(
+    text: &'a str,
+    arena: &'a Arena<AstNode<'a>>,
+    cfg: &ManOutputConfiguration,
+    links: &ChapterLinks,
+    role: ChapterRole,
+) -> RenderedChapter {
+    let (abbreviations, text) = preprocess(text, cfg);
+    let text = text.as_ref();
+    let mut parser = Parser {
+        abbreviations,
+        synopsis: role == ChapterRole::Synopsis,
+        max_depth: cfg.max_depth.unwrap_or(DEFAULT_MAX_DEPTH),
+        keep_styled_whitespace: cfg.keep_styled_whitespace,
+        break_words: cfg.break_long_words,
+        truncate_urls: cfg.truncate_urls,
+        kbd_style: cfg.kbd_style,
+        ..Default::default()
+    };
+    let root = parse_document(arena, text, &build_comrak_options(cfg));
+    if cfg.quote_attributions {
+        quote_attributions(root, arena);
+    }
+    let examples = cfg
+        .examples
+        .then(|| example_blocks(root, arena, cfg.example_tag()));
+    let usage = match role {
+        ChapterRole::Intro => usage_block(root).and_then(|node| {
+            node.detach();
+            match &node.data.borrow().value {
+                NodeValue::CodeBlock(block) => Some(synopsis_block(block)),
+                _ => None,
+            }
+        }),
+        _ => None,
+    };
+    let title = leading_title(root, cfg);
+
+    let render = |node: &'a AstNode<'a>, parser: &mut Parser, entering: bool| {
+        let value = &node.data.borrow().value;
+        if !entering {
+            if matches!(
+                value,
+                NodeValue::Paragraph | NodeValue::Heading(_) | NodeValue::TableCell
+            ) {
+                // inline elements don't reach past the end of their block
+                parser.close_elements();
+            }
+            match value {
+                NodeValue::Strong | NodeValue::Emph => parser.pop_style(),
+                NodeValue::Superscript => {
+                    let text = parser.styled("^");
+                    parser.append_roff(text);
+                }
+                NodeValue::List(_) if nested_list(node) => {
+                    parser.append_roff(raw::request(".RE"));
+                    parser.trim_next = true;
+                }
+                NodeValue::DescriptionDetails => {
+                    let content = parser.end_group();
+                    let term = parser.term.take().unwrap_or_default();
+                    parser.append_roff(RoffNode::tagged_paragraph(content, term.roff().bold()));
+                }
+                NodeValue::Strikethrough => match cfg.strikethrough_style {
+                    StrikethroughStyle::Brackets => {
+                        let text = parser.styled("-]");
+                        parser.append_roff(text);
+                    }
+                    StrikethroughStyle::Overstrike => parser.overstrike -= 1,
+                },
+                NodeValue::Paragraph if leads_item(node) => {}
+                NodeValue::Paragraph if continues_details(node) => {
+                    // keeps the indentation of the details
+                    let content = parser.end_group();
+                    parser.append_roff(RoffNode::indented_paragraph(content, None, None::<&str>));
+                }
+                NodeValue::Paragraph if continues_item(node) => {
+                    let content = parser.end_group();
+                    let indent = node
+                        .parent()
+                        .map_or(cfg.list_indent.unwrap_or(ITEM_INDENT), |item| {
+                            item_indent(item, cfg)
+                        });
+                    parser.append_roff(RoffNode::indented_paragraph(
+                        content,
+                        Some(indent),
+                        None::<&str>,
+                    ));
+                }
+                NodeValue::Item(_) if option_flag(node).is_some() => {
+                    let content = parser.end_group();
+                    let flag = escape::code(&option_flag(node).unwrap_or_default());
+                    parser.append_roff(RoffNode::tagged_paragraph(content, flag.roff().bold()));
+                }
+                NodeValue::Item(_) => {
+                    if let Some(marker) = item_marker(node, cfg) {
+                        let content = parser.end_group();
+                        parser.append_roff(RoffNode::indented_paragraph(
+                            content,
+                            Some(item_indent(node, cfg)),
+                            Some(marker),
+                        ));
+                    }
+                }
+                NodeValue::Paragraph => {
+                    let content = parser.end_group();
+                    parser.append_roff(RoffNode::paragraph(content));
+                }
+                NodeValue::TableCell => {
+                    let cell = parser.end_group();
+                    if let Some(row) = parser.table.last_mut() {
+                        row.cells.push(cell);
+                    }
+                }
+                NodeValue::Table(alignments) => {
+                    let rows = std::mem::take(&mut parser.table);
+                    for node in table::tbl(alignments, rows) {
+                        parser.append_roff(node);
+                    }
+                }
+                NodeValue::Heading(_) => {
+                    parser.pop_style();
+                    let content = parser.end_group();
+                    let mut text = String::new();
+                    text_content(node, &mut text);
+                    if cfg.keep_together {
+                        // the heading with the first lines of the text following it
+                        parser.append_roff(raw::request(format!(".ne {}", HEADING_NEED_LINES)));
+                    }
+                    for node in heading(content, text.chars().count()) {
+                        parser.append_roff(node);
+                    }
+                }
+                // the address of the outer link or image is the only one given
+                NodeValue::Link(_) | NodeValue::Image(_) if inside_link(node) => {}
+                // the content of the link was already rendered as the display text
+                NodeValue::Link(ref link) => {
+                    let url = String::from_utf8_lossy(link.url.as_slice());
+                    match links.reference(&url) {
+                        Some(reference) => reference
+                            .into_iter()
+                            .for_each(|node| parser.append_roff(node)),
+                        None => parser.append_url(&url, true),
+                    }
+                }
+                NodeValue::Image(ref link) => {
+                    parser.append_url(&String::from_utf8_lossy(link.url.as_slice()), true);
+                }
+                _ => {}
+            }
+            return true;
+        }
+        if matches!(
+            value,
+            NodeValue::CodeBlock(_) | NodeValue::Table(_) | NodeValue::BlockQuote
+        ) && follows_list(node)
+        {
+            // `.PD 0` keeps the paragraph ending the list from adding space of its own
+            parser.append_roff(raw::request(".PD 0\n.P\n.PD"));
+        }
+        match value {
+            NodeValue::Strong => parser.push_style(FontStyle::Bold),
+            NodeValue::Emph => parser.push_style(FontStyle::Italic),
+            NodeValue::Superscript => {
+                let text = parser.styled("^");
+                parser.append_roff(text);
+            }
+            NodeValue::DescriptionTerm => {
+                let mut text = String::new();
+                text_content(node, &mut text);
+                parser.term = Some(text.trim().to_string());
+                return false;
+            }
+            NodeValue::DescriptionDetails => parser.begin_group(),
+            NodeValue::List(_) if nested_list(node) => {
+                parser.append_roff(raw::request(".RS"));
+            }
+            NodeValue::Strikethrough => match cfg.strikethrough_style {
+                StrikethroughStyle::Brackets => {
+                    let text = parser.styled("[-");
+                    parser.append_roff(text);
+                }
+                StrikethroughStyle::Overstrike => parser.overstrike += 1,
+            },
+            NodeValue::Image(ref link) if cfg.pspic_images => {
+                let url = String::from_utf8_lossy(link.url.as_slice());
+                if let Some(path) = links.local_file(&url) {
+                    let path = path.to_string_lossy().replace('"', "\\(dq");
+                    parser.append_roff(raw::request(format!(".PSPIC \"{}\"", path)));
+                    parser.trim_next = true;
+                    return false;
+                }
+            }
+            NodeValue::Link(ref link) | NodeValue::Image(ref link) if !inside_link(node) => {
+                let url = String::from_utf8_lossy(link.url.as_slice());
+                let mut text = String::new();
+                text_content(node, &mut text);
+                if text.is_empty() || text == url || format!("mailto:{}", text) == url {
+                    // autolinks display the address itself
+                    parser.append_url(&url, false);
+                    parser.update_last_node(MarkdownNode::from(value));
+                    return false;
+                }
+            }
+            // the option is the tag of its item
+            NodeValue::Code(_) if option_code(node) => return false,
+            NodeValue::Code(code) if math::inline_source(&code.literal).is_some() => {
+                let source = math::inline_source(&code.literal).unwrap_or_default();
+                let source = String::from_utf8_lossy(source);
+                // the source of inline math is set in roman whatever the enclosing styles are
+                let styles = std::mem::take(&mut parser.styles);
+                let text = parser.styled_verbatim(&escape::code(&source));
+                parser.styles = styles;
+                parser.append_roff(text);
+            }
+            NodeValue::Code(code) => {
+                let text = escape::code(&String::from_utf8_lossy(code.literal.as_slice()));
+                // the code keeps the styles of the span it is part of
+                let quoted = cfg.inline_code_style == InlineCodeStyle::Quoted;
+                if quoted {
+                    let quote = parser.styled("`");
+                    parser.append_roff(quote);
+                }
+                parser.push_style(match cfg.inline_code_style {
+                    InlineCodeStyle::Bold => FontStyle::Bold,
+                    InlineCodeStyle::Quoted | InlineCodeStyle::Italic => FontStyle::Italic,
+                });
+                let code = parser.styled_verbatim(&text);
+                parser.pop_style();
+                parser.append_roff(code);
+                if quoted {
+                    let quote = parser.styled("`");
+                    parser.append_roff(quote);
+                }
+            }
+            NodeValue::CodeBlock(ref block) => {
+                if parser.synopsis {
+                    parser.append_roff(synopsis_block(block));
+                } else if !cfg.omit_code_blocks {
+                    let lines = String::from_utf8_lossy(&block.literal).lines().count();
+                    if cfg.keep_together && lines <= MAX_KEPT_EXAMPLE_LINES {
+                        parser.append_roff(raw::request(format!(".ne {}", lines + 1)));
+                    }
+                    parser.append_roff(code_block(block, cfg));
+                } else if let Some(placeholder) = &cfg.code_block_placeholder {
+                    parser.append_roff(RoffNode::paragraph([placeholder.roff().italic()]));
+                }
+            }
+            NodeValue::Text(ref text) => {
+                let text = String::from_utf8_lossy(text);
+                let text = match node.previous_sibling() {
+                    // the description of an option follows the colon after it
+                    Some(code) if option_code(code) => text[1..].trim_start().into(),
+                    _ => text,
+                };
+                let text = unresolved_images(&text);
+                let text = parser.expand_abbreviations(text);
+                let text = if parser.overstrike > 0 {
+                    overstrike(&text).into()
+                } else {
+                    text
+                };
+                match parser.last_node() {
+                    MarkdownNode::ListItem => {
+                        let text = parser.styled(&text);
+                        parser.append_roff(text);
+                        parser.append_roff(RoffNode::linebreak());
+                    }
+                    _ => {
+                        let text = parser.styled(&text);
+                        parser.append_roff(text);
+                    }
+                }
+            }
+            NodeValue::HtmlBlock(block) => {
+                let html = String::from_utf8_lossy(&block.literal);
+                match details_block(&html) {
+                    // there is nothing to expand in a page, the content is always shown
+                    Some(details) => {
+                        if details.open {
+                            parser.open_details();
+                        }
+                        if let Some(summary) = details.summary.filter(|s| !s.is_empty()) {
+                            parser.label_details(&summary);
+                        }
+                        if !details.text.is_empty() {
+                            let text = parser.styled(&details.text);
+                            parser.append_roff(RoffNode::paragraph([text]));
+                        }
+                        if details.close {
+                            parser.close_details();
+                        }
+                    }
+                    None if cfg.unsafe_html => {
+                        parser.append_roff(raw_html_block(&html));
+                        parser.trim_next = true;
+                    }
+                    None => unhandled_node(value, source_line(node), parser, cfg),
+                }
+            }
+            NodeValue::HtmlInline(html) if cfg.unsafe_html => {
+                let html = parser.styled(&String::from_utf8_lossy(html));
+                parser.append_roff(html);
+            }
+            NodeValue::HtmlInline(html) if inline_element(html).is_some() => {
+                let (element, open) = match inline_element(html) {
+                    Some(tag) => tag,
+                    None => return true,
+                };
+                if element == InlineElement::Break {
+                    if open {
+                        parser.append_roff(RoffNode::linebreak());
+                    }
+                    return true;
+                }
+                if !open && parser.elements.last() != Some(&element) {
+                    // a closing tag without an opening one has nothing to end
+                    return true;
+                }
+                parser.toggle_element(element, open);
+            }
+            NodeValue::Table(_) if cfg.table_style == TableStyle::Plain => {
+                parser.append_roff(table::plain(node));
+                return false;
+            }
+            NodeValue::Table(_) => parser.table.clear(),
+            NodeValue::TableRow(header) => parser.table.push(table::Row {
+                header: *header,
+                cells: vec![],
+            }),
+            NodeValue::TableCell => parser.begin_group(),
+            NodeValue::Paragraph if leads_item(node) => {}
+            NodeValue::Paragraph if cfg.table_captions && table_caption(node).is_some() => {
+                let caption = table_caption(node).unwrap_or_default();
+                parser.append_roff(raw::request(".ce 1"));
+                parser.append_roff(caption.roff().bold());
+                return false;
+            }
+            NodeValue::Paragraph
+                if !cfg.keep_empty_paragraphs
+                    && empty_paragraph(node)
+                    && node.previous_sibling().is_some_and(empty_paragraph) =>
+            {
+                return false;
+            }
+            NodeValue::Paragraph => match definition(node, text) {
+                Some((term, description)) if cfg.detect_definitions => {
+                    parser.append_roff(RoffNode::tagged_paragraph(
+                        [description],
+                        term.roff().bold(),
+                    ));
+                    return false;
+                }
+                _ => parser.begin_group(),
+            },
+            NodeValue::SoftBreak if cfg.hard_breaks => parser.append_roff(RoffNode::linebreak()),
+            NodeValue::SoftBreak => {
+                // the lines of a paragraph are filled as one
+                let space = parser.styled(" ");
+                parser.append_roff(space);
+            }
+            NodeValue::LineBreak => {
+                parser.append_roff(RoffNode::linebreak());
+            }
+            NodeValue::Heading(_) => {
+                let mut text = String::new();
+                text_content(node, &mut text);
+                if text.trim().is_empty() {
+                    // an empty heading would only leave an empty bold line behind
+                    return false;
+                }
+                parser.push_style(FontStyle::Bold);
+                parser.begin_group();
+            }
+            NodeValue::TaskItem(checked) => {
+                // the marker starts the paragraph of the item
+                let marker = if *checked {
+                    cfg.task_checked.as_deref().unwrap_or("[x]")
+                } else {
+                    cfg.task_unchecked.as_deref().unwrap_or("[ ]")
+                };
+                parser.append_roff(format!("{} ", marker));
+            }
+            NodeValue::FootnoteReference(ref number)
+                if cfg.footnote_style == FootnoteStyle::Inline =>
+            {
+                if let Some(definition) = footnote_definition(node, number) {
+                    let mut text = String::new();
+                    text_content(definition, &mut text);
+                    let text = parser.styled(&format!(" ({})", text.trim()));
+                    parser.append_roff(text);
+                }
+            }
+            NodeValue::FootnoteReference(ref number) => {
+                let text = format!("[{}]", String::from_utf8_lossy(number));
+                let text = parser.styled(&text);
+                parser.append_roff(text);
+            }
+            // the definitions were already rendered where they are referenced
+            NodeValue::FootnoteDefinition(_) if cfg.footnote_style == FootnoteStyle::Inline => {
+                return false;
+            }
+            NodeValue::FootnoteDefinition(ref number) => {
+                // comrak moves referenced definitions to the end of the document, ordered and
+                // renamed by the number of their first reference
+                let mut text = String::new();
+                text_content(node, &mut text);
+                let number = format!("[{}]", String::from_utf8_lossy(number));
+                parser.append_note(RoffNode::indented_paragraph(
+                    [text.trim()],
+                    Some(4),
+                    Some(number),
+                ));
+                return false;
+            }
+            NodeValue::Item(_)
+                if option_flag(node).is_some() || item_marker(node, cfg).is_some() =>
+            {
+                parser.begin_group();
+            }
+            // containers without a representation of their own still render their children
+            NodeValue::Document => {}
+            _ if node.first_child().is_none() => {
+                unhandled_node(value, source_line(node), parser, cfg)
+            }
+            _ => {}
+        }
+
+        parser.update_last_node(MarkdownNode::from(value));
+        true
+    };
+    iter_nodes(root, &mut parser, &render);
+    // `<details>` elements left open end with the chapter
+    while parser.close_details() {}
+    if let Some(examples) = examples {
+        parser.begin_group();
+        iter_nodes(examples, &mut parser, &render);
+        parser.examples = parser.end_group();
+    }
+    let code_only = root.children().next().is_some()
+        && root
+            .children()
+            .all(|node| matches!(node.data.borrow().value, NodeValue::CodeBlock(_)));
+    if parser.truncated {
+        eprintln!(
+            "mdbook-man: warning: skipped content nested deeper than {} levels",
+            parser.max_depth
+        );
+    }
+    RenderedChapter {
+        code_only,
+        usage,
+        title,
+        ..parser.finalize()
+    }
+}
+
+fn chapter_separator(separator: ChapterSeparator) -> Option<RoffNode> {
+    match separator {
+        ChapterSeparator::None => None,
+        ChapterSeparator::Rule => Some(raw::request(".sp\n\\l'\\n(.lu'")),
+        ChapterSeparator::Space => Some(raw::request(".sp 2")),
+        ChapterSeparator::Comment => Some(raw::request(".\\\" end of chapter")),
+    }
+}
+
+/// Creates a page titled `name` with the remaining fields of the `.TH` header that are shared by
+/// all pages filled from the configuration.
+fn new_page(name: &str, cfg: &ManOutputConfiguration) -> Roff {
+    let page = Roff::new(name, SectionNumber::Custom(cfg.section_number()));
+    let manual = cfg.manual_title();
+    if cfg.date.is_none() && cfg.source.is_none() && manual.is_none() {
+        return page;
+    }
+    // roffman only knows about the date field of the header, the remaining fields are appended
+    // to it so that every field ends up quoted on its own
+    let field = |field: Option<&str>| field.unwrap_or_default().replace('"', "\\(dq");
+    page.date(format!(
+        "{}\" \"{}\" \"{}",
+        field(cfg.date.as_deref()),
+        field(cfg.source.as_deref()),
+        field(manual)
+    ))
+}
+
+/// Returns the first sentence of the first paragraph of `markdown`.
+fn first_sentence(markdown: &str, cfg: &ManOutputConfiguration) -> Option<String> {
+    let arena = Arena::new();
+    let markdown = frontmatter::strip(markdown);
+    let root = parse_document(&arena, &markdown, &build_comrak_options(cfg));
+    let paragraph = root
+        .children()
+        .find(|node| matches!(node.data.borrow().value, NodeValue::Paragraph))?;
+    let mut text = String::new();
+    text_content(paragraph, &mut text);
+
+    let end = text
+        .char_indices()
+        .find(|&(i, c)| {
+            matches!(c, '.' | '!' | '?')
+                && text[i + 1..].chars().next().is_none_or(char::is_whitespace)
+        })
+        .map_or(text.len(), |(i, _)| i);
+    let sentence = text[..end].split_whitespace().collect::<Vec<_>>().join(" ");
+    (!sentence.is_empty()).then_some(sentence)
+}
+
+/// Returns the `NAME` section of a page called `name`, summarized by the description of the
+/// book or else by the first sentence of the `first` chapter.
+fn name_section(
+    ctx: &RenderContext,
+    name: &str,
+    first: Option<&Chapter>,
+    cfg: &ManOutputConfiguration,
+) -> Vec<RoffNode> {
+    let summary = match &ctx.config.book.description {
+        Some(description) => Some(description.trim().to_string()),
+        None => first.and_then(|ch| first_sentence(&ch.content, cfg)),
+    };
+    let line = match summary.filter(|summary| !summary.is_empty()) {
+        Some(summary) => format!("{} - {}", name, summary),
+        None => name.to_string(),
+    };
+    vec![RoffNode::paragraph([line])]
+}
+
+/// Returns the `AUTHORS` section listing the authors of the book one per line, if it has any.
+/// Authors written as `Name <email>` get a link to their address.
+fn authors_section(ctx: &RenderContext) -> Option<Vec<RoffNode>> {
+    let authors = &ctx.config.book.authors;
+    if authors.is_empty() {
+        return None;
+    }
+    let mut nodes = vec![];
+    for (i, author) in authors.iter().enumerate() {
+        if i > 0 {
+            nodes.push(RoffNode::linebreak());
+        }
+        let author = author.trim();
+        let address = author
+            .strip_suffix('>')
+            .and_then(|rest| rest.rsplit_once('<'));
+        match address {
+            Some((name, email)) => {
+                nodes.push(raw::request(format!(".MT {}", email.trim())));
+                let name = name.trim();
+                if !name.is_empty() {
+                    nodes.push(RoffNode::text(name));
+                }
+                nodes.push(raw::request(".ME"));
+            }
+            None => nodes.push(RoffNode::text(author)),
+        }
+    }
+    Some(vec![RoffNode::paragraph(nodes)])
+}
+
+/// Returns the paragraph listing the full `urls` of shortened links one per line, if there are
+/// any.
+fn url_list(urls: &[String]) -> Option<RoffNode> {
+    let mut nodes = vec![];
+    for (i, url) in urls.iter().enumerate() {
+        if i > 0 {
+            // a linebreak node would leave an empty line after the `.UE` ending its line
+            nodes.push(raw::request(".br"));
+        }
+        nodes.push(RoffNode::url("", url.as_str()));
+    }
+    (!nodes.is_empty()).then(|| RoffNode::paragraph(nodes))
+}
+
+/// Requests emitted at the top of every page before any content.
+fn preamble(cfg: &ManOutputConfiguration) -> Vec<RoffNode> {
+    let mut nodes = vec![];
+    if let Some(length) = cfg.line_length {
+        // the man macros restore the line length from the `LL` register, setting both keeps it
+        // wherever they do
+        nodes.push(raw::request(format!(".nr LL {}n\n.ll {}n", length, length)));
+    }
+    if cfg.ragged_right {
+        nodes.push(raw::request(".na"));
+    }
+    if cfg.no_hyphenation {
+        nodes.push(raw::request(".nh"));
+    }
+    nodes
+}
+
+/// Renders a `Roff` created by this crate to a `String`. This should be preferred over
+/// `Roff::to_string` as some of the requests are only put in place by this function.
+pub fn roff_to_string(roff: &Roff) -> Result<String, Error> {
+    Ok(raw::expand(&roff.to_string()?))
+}
+
+/// Renders a page created by this crate from the book of `ctx` to a `String` written in the
+/// configured macro package. If `check` is enabled the page is checked with
+/// [`lint_page`](lint_page) first.
+pub fn page_to_string(ctx: &RenderContext, page: &Roff) -> Result<String, Error> {
+    let cfg = ManOutputConfiguration::load(ctx)?;
+    let book = &ctx.config.book;
+    let description = book
+        .description
+        .as_deref()
+        .or(book.title.as_deref())
+        .unwrap_or_default();
+    finish_page(roff_to_string(page)?, &cfg, description, &ctx.version)
+}
+
+/// Requests of the header of a page in the `man` and the `mdoc` macro packages.
+const HEADER_REQUESTS: &[&str] = &[".TH ", ".Dd ", ".Dt ", ".Os"];
+
+/// Returns the `rendered` page without the requests of its header, leaving the sections to be
+/// included in a page with a header of its own.
+fn strip_header(rendered: &str) -> String {
+    let mut lines = rendered.split_inclusive('\n').peekable();
+    let mut out = String::with_capacity(rendered.len());
+    // the header only ever follows the comment lines starting the page
+    while let Some(line) =
+        lines.next_if(|line| line.starts_with(".\\\"") || line.starts_with("'\\\""))
+    {
+        out.push_str(line);
+    }
+    let is_header = |line: &&str| {
+        HEADER_REQUESTS
+            .iter()
+            .any(|request| line.starts_with(request))
+    };
+    out.extend(lines.skip_while(is_header));
+    out
+}
+
+/// Converts the `rendered` page to the configured macro package and adds the header lines of
+/// the page. `description` summarizes `mdoc` pages without a `NAME` section.
+fn finish_page(
+    rendered: String,
+    cfg: &ManOutputConfiguration,
+    description: &str,
+    mdbook_version: &str,
+) -> Result<String, Error> {
+    let rendered = match cfg.macro_package {
+        MacroPackage::Man => rendered,
+        MacroPackage::Mdoc => mdoc::from_man(&rendered, description),
+    };
+    let rendered = escape::non_ascii(&rendered, cfg.target).into_owned();
+    let tables = rendered.contains("\n.TS\n");
+    let rendered = if cfg.generation_comment {
+        format!(
+            ".\\\" generated by {} {} from mdbook {}\n{}",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION"),
+            mdbook_version,
+            rendered
+        )
+    } else {
+        rendered
+    };
+    // man only reads the preprocessors of the page from its very first line
+    let rendered = if tables {
+        format!("{}\n{}", table::PREPROCESSOR_LINE, rendered)
+    } else {
+        rendered
+    };
+    if cfg.check {
+        let issues = lint_page(&rendered);
+        if !issues.is_empty() {
+            return Err(Error::Lint(issues));
+        }
+    }
+    if cfg.body_only {
+        return Ok(strip_header(&rendered));
+    }
+    Ok(rendered)
+}
+
+/// Renders the Markdown `text` as a man page configured by `cfg` without the need for a book.
+/// The content is put in the `DESCRIPTION` section of a page named after `page-name`, or
+/// `untitled` if it isn't set.
+pub fn render_markdown(text: &str, cfg: &ManOutputConfiguration) -> Result<String, Error> {
+    cfg.validate()?;
+    let arena = Arena::new();
+    let links = ChapterLinks::new(iter::empty(), ReferenceStyle::Section, |ch| ch.name.clone());
+    let mut rendered = markdown_to_roff(text, &arena, cfg, &links, ChapterRole::Body);
+    let name = cfg.page_name.as_deref().unwrap_or("untitled");
+    rendered.check_strict(name, cfg)?;
+    let examples = std::mem::take(&mut rendered.examples);
+    let urls = std::mem::take(&mut rendered.urls);
+    let mut nodes = preamble(cfg);
+    nodes.extend(rendered.into_nodes());
+    let mut page = new_page(name, cfg).section("DESCRIPTION", nodes);
+    if !examples.is_empty() {
+        page = page.section("EXAMPLES", examples);
+    }
+    if let Some(list) = url_list(&urls) {
+        page = page.section("SEE ALSO", [list]);
+    }
+
+    let description = first_sentence(text, cfg).unwrap_or_default();
+    finish_page(
+        roff_to_string(&page)?,
+        cfg,
+        &description,
+        mdbook::MDBOOK_VERSION,
+    )
+}
+
+/// Renders the whole book as a single man page with a section per chapter. Use
+/// [`roff_to_string`](roff_to_string) to render the returned page.
+pub fn mdbook_to_roff(ctx: &RenderContext) -> Result<Roff, Error> {
+    let cfg = ManOutputConfiguration::load(ctx)?;
+    let arena = Arena::new();
+    let name = cfg
+        .page_name
+        .as_deref()
+        .or(ctx.config.book.title.as_deref())
+        .unwrap_or_default();
+    let mut page = new_page(name, &cfg);
+
+    let chapters = book_chapters(ctx, &cfg)?;
+    let chapter_sections = chapter_sections(ctx, &chapters, &cfg);
+    // links refer to the sections by the titles they end up with
+    let mut links = ChapterLinks::new(chapters.iter().copied(), ReferenceStyle::Section, |ch| {
+        chapters
+            .iter()
+            .zip(&chapter_sections)
+            .find(|(other, _)| std::ptr::eq(**other, ch))
+            .map(|(_, section)| section.title.clone())
+            .unwrap_or_default()
+    })
+    .source_dir(ctx.source_dir());
+
+    // sections are collected first as chapters may be merged into an earlier section
+    let mut sections: Vec<(String, Vec<RoffNode>)> = vec![];
+    let mut examples = vec![];
+    let mut urls: Vec<String> = vec![];
+    for (i, (ch, section)) in chapters.iter().zip(chapter_sections).enumerate() {
+        let role = ChapterRole::of(&chapters, i, &cfg);
+        links.enter(ch);
+        let mut rendered = markdown_to_roff(ch.content.as_str(), &arena, &cfg, &links, role);
+        rendered.check_strict(&ch.name, &cfg)?;
+        let usage = rendered.usage.take();
+        examples.append(&mut rendered.examples);
+        for url in rendered.urls.drain(..) {
+            if !urls.contains(&url) {
+                urls.push(url);
+            }
+        }
+        let mut parsed = rendered.into_nodes();
+        if let Some(usage) = usage {
+            sections.push(("SYNOPSIS".into(), vec![usage]));
+        }
+        if i + 1 < chapters.len() {
+            parsed.extend(chapter_separator(cfg.chapter_separator));
+        }
+
+        if section.merged {
+            if let Some((_, nodes)) = sections
+                .iter_mut()
+                .find(|(other, _)| *other == section.title)
+            {
+                nodes.extend(parsed);
+            }
+        } else {
+            sections.push((section.title, parsed));
+        }
+    }
+    let mut head = preamble(&cfg);
+    // a NAME section written by hand takes the place of the generated one
+    match sections
+        .iter()
+        .position(|(title, _)| title.eq_ignore_ascii_case("NAME"))
+    {
+        Some(i) => head.extend(sections.remove(i).1),
+        None => head.extend(name_section(ctx, name, chapters.first().copied(), &cfg)),
+    }
+    page = page.section("NAME", head);
+    if !examples.is_empty() {
+        // examples are added to a chapter of the same name instead of a section of their own
+        match sections
+            .iter_mut()
+            .find(|(title, _)| title.eq_ignore_ascii_case("EXAMPLES"))
+        {
+            Some((_, nodes)) => nodes.extend(examples),
+            None => sections.push(("EXAMPLES".into(), examples)),
+        }
+    }
+    let banner = ctx
+        .config
+        .book
+        .title
+        .as_deref()
+        .filter(|_| cfg.title_banner);
+    if let (Some(banner), Some((_, nodes))) = (banner, sections.first_mut()) {
+        nodes.splice(
+            0..0,
+            [raw::request(".ce 1"), banner.roff().bold().into_roff()],
+        );
+    }
+    if let Some(list) = url_list(&urls) {
+        match sections
+            .iter_mut()
+            .find(|(title, _)| title.eq_ignore_ascii_case("SEE ALSO"))
+        {
+            Some((_, nodes)) => nodes.push(list),
+            None => sections.push(("SEE ALSO".into(), vec![list])),
+        }
+    }
+    for (title, nodes) in sections {
+        page = page.section(title, nodes);
+    }
+    if let Some(authors) = authors_section(ctx) {
+        page = page.section("AUTHORS", authors);
+    }
+
+    Ok(page)
+}
+
+/// Renders the whole book as a single man page and returns the resulting roff as a `String`.
+pub fn mdbook_to_string(ctx: &RenderContext) -> Result<String, Error> {
+    page_to_string(ctx, &mdbook_to_roff(ctx)?)
+}
+
+/// Renders the whole book as a single man page and writes the resulting roff to `writer`.
+pub fn mdbook_to_writer(ctx: &RenderContext, mut writer: impl io::Write) -> Result<(), Error> {
+    writer.write_all(mdbook_to_string(ctx)?.as_bytes())?;
+    Ok(())
+}
+
+/// Renders the whole book as a single man page and returns the bytes of the resulting roff.
+pub fn mdbook_to_bytes(ctx: &RenderContext) -> Result<Vec<u8>, Error> {
+    let mut bytes = vec![];
+    mdbook_to_writer(ctx, &mut bytes)?;
+    Ok(bytes)
+}
+
+/// Collects all chapters of the `book` including nested ones in the order of the summary.
+/// Separators and part titles are skipped so that they never take up a chapter index.
+fn all_chapters(book: &Book) -> Vec<&Chapter> {
+    book.iter()
+        .filter_map(|item| match item {
+            BookItem::Chapter(ch) => Some(ch),
+            BookItem::Separator | BookItem::PartTitle(_) => None,
+        })
+        .collect()
+}
+
+/// Returns the title of the part of the `book` the `chapter` is in, if it is in any.
+fn part_title<'a>(book: &'a Book, chapter: &Chapter) -> Option<&'a str> {
+    fn contains(ch: &Chapter, chapter: &Chapter) -> bool {
+        std::ptr::eq(ch, chapter)
+            || ch.sub_items.iter().any(|item| match item {
+                BookItem::Chapter(ch) => contains(ch, chapter),
+                _ => false,
+            })
+    }
+
+    let mut part = None;
+    for item in &book.sections {
+        match item {
+            BookItem::PartTitle(title) => part = Some(title.as_str()),
+            BookItem::Chapter(ch) if contains(ch, chapter) => return part,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Returns all chapters of the book in the configured order, skipping separators and part
+/// titles.
+fn book_chapters<'a>(
+    ctx: &'a RenderContext,
+    cfg: &ManOutputConfiguration,
+) -> Result<Vec<&'a Chapter>, Error> {
+    let mut chapters = all_chapters(&ctx.book);
+    if cfg.affix_chapters == AffixChapters::Skip {
+        // only prefix and suffix chapters are left without a number by mdBook
+        chapters.retain(|ch| ch.number.is_some());
+    }
+
+    match &cfg.chapter_order {
+        ChapterOrder::Summary => {}
+        ChapterOrder::Alphabetical => {
+            chapters.sort_by_cached_key(|ch| ch.name.to_lowercase());
+        }
+        ChapterOrder::Explicit(names) => {
+            let mut ordered = Vec::with_capacity(chapters.len());
+            for name in names {
+                let pos = chapters
+                    .iter()
+                    .position(|ch| &ch.name == name)
+                    .ok_or_else(|| Error::ChapterNotFound {
+                        selector: name.clone(),
+                        count: chapters.len() + ordered.len(),
+                    })?;
+                ordered.push(chapters.remove(pos));
+            }
+            ordered.extend(chapters);
+            chapters = ordered;
+        }
+    }
+
+    Ok(chapters)
+}
+
+/// Picks the chapters matching `selectors` in the order of the selectors.
+fn select_chapters<'a>(
+    chapters: &[&'a Chapter],
+    selectors: &[ChapterSelector],
+) -> Result<Vec<&'a Chapter>, Error> {
+    selectors
+        .iter()
+        .map(|selector| {
+            let chapter = match selector {
+                ChapterSelector::Index(index) => chapters.get(*index),
+                ChapterSelector::Name(name) => chapters.iter().find(|ch| &ch.name == name),
+            };
+            chapter.copied().ok_or_else(|| Error::ChapterNotFound {
+                selector: selector.to_string(),
+                count: chapters.len(),
+            })
+        })
+        .collect()
+}
+
+fn render_chapter_pages(
+    ctx: &RenderContext,
+    chapters: &[&Chapter],
+    cfg: &ManOutputConfiguration,
+) -> Result<Vec<(String, Roff)>, Error> {
+    let arena = Arena::new();
+    let matters = chapters
+        .iter()
+        .map(|ch| FrontMatter::of(ch))
+        .collect::<Result<Vec<_>, _>>()?;
+    let page_name = |ch: &Chapter| match FrontMatter::of(ch) {
+        Ok(FrontMatter {
+            page_name: Some(name),
+            ..
+        }) => name,
+        _ => ch.name.clone(),
+    };
+    let mut links = ChapterLinks::new(
+        chapters.iter().copied(),
+        ReferenceStyle::Page(cfg.section_number()),
+        page_name,
+    )
+    .sections(chapters.iter().copied(), |ch| {
+        FrontMatter::of(ch).ok().and_then(|matter| matter.section)
+    })
+    .source_dir(ctx.source_dir());
+    let index = index(cfg);
+    let mut pages = vec![];
+    // names and sections of the pages listed by the index
+    let mut references = vec![];
+    for (i, (ch, matter)) in chapters.iter().zip(&matters).enumerate() {
+        // the front matter of the chapter takes precedence over the configuration of the book
+        let cfg = &matter.apply(cfg);
+        let name = matter.page_name.as_deref().unwrap_or(&ch.name);
+        let mut page = new_page(name, cfg);
+        let role = ChapterRole::of(chapters, i, cfg);
+        links.enter(ch);
+        let mut rendered = markdown_to_roff(ch.content.as_str(), &arena, cfg, &links, role);
+        rendered.check_strict(&ch.name, cfg)?;
+        if rendered.code_only && cfg.name_code_chapters {
+            // without any prose the page would be nothing but the code
+            let book = &ctx.config.book;
+            let description = book.description.as_deref().or(book.title.as_deref());
+            let name = match description {
+                Some(description) => format!("{} - {}", ch.name, description),
+                None => ch.name.clone(),
+            };
+            page = page.section("NAME", [RoffNode::paragraph([name])]);
+        }
+        if let Some(usage) = rendered.usage.take() {
+            page = page.section("SYNOPSIS", [usage]);
+        }
+        let mut parsed = preamble(cfg);
+        let title = match &matter.section_title {
+            Some(title) => title.clone(),
+            None => rendered.section_title(ctx, ch, cfg),
+        };
+        parsed.extend(rendered.body);
+        page = page.section(title, parsed);
+        if !rendered.notes.is_empty() {
+            page = page.section("NOTES", rendered.notes);
+        }
+        if !rendered.examples.is_empty() {
+            page = page.section("EXAMPLES", rendered.examples);
+        }
+        let mut see_also = vec![];
+        if let Some((index, section)) = &index {
+            see_also.push(RoffNode::paragraph(page_reference(index, *section)));
+        }
+        see_also.extend(url_list(&rendered.urls));
+        if !see_also.is_empty() {
+            page = page.section("SEE ALSO", see_also);
+        }
+        if let Some(authors) = authors_section(ctx) {
+            page = page.section("AUTHORS", authors);
+        }
+        pages.push((ch.name.clone(), page));
+        references.push((name.to_string(), cfg.section_number()));
+    }
+
+    if let Some((index, section)) = index {
+        let page = index_page(
+            ctx,
+            &index,
+            section,
+            chapters.first().copied(),
+            &references,
+            cfg,
+        );
+        pages.push((index, page));
+    }
+
+    Ok(pages)
+}
+
+/// Returns the nodes referencing the page `name` in `section` like `mytool(1)`.
+fn page_reference(name: &str, section: u8) -> Vec<RoffNode> {
+    vec![
+        name.roff().bold().into_roff(),
+        format!("({})", section).into_roff(),
+    ]
+}
+
+/// Returns the name and the section of the configured index page.
+fn index(cfg: &ManOutputConfiguration) -> Option<(String, u8)> {
+    let name = cfg.index_page.clone()?;
+    Some((name, cfg.index_section.unwrap_or(cfg.section_number())))
+}
+
+/// Renders the index page `name` of split chapters in `section`, it lists the `pages` of all
+/// chapters by their name and section in its `SEE ALSO` section.
+fn index_page(
+    ctx: &RenderContext,
+    name: &str,
+    section: u8,
+    first: Option<&Chapter>,
+    pages: &[(String, u8)],
+    cfg: &ManOutputConfiguration,
+) -> Roff {
+    let cfg = ManOutputConfiguration {
+        section: Some(section),
+        ..cfg.clone()
+    };
+    let mut head = preamble(&cfg);
+    head.extend(name_section(ctx, name, first, &cfg));
+    let mut page = new_page(name, &cfg).section("NAME", head);
+
+    let mut references = vec![];
+    for (i, (page, section)) in pages.iter().enumerate() {
+        if i > 0 {
+            references.push(", ".into_roff());
+        }
+        references.extend(page_reference(page, *section));
+    }
+    if !references.is_empty() {
+        page = page.section("SEE ALSO", [RoffNode::paragraph(references)]);
+    }
+    if let Some(authors) = authors_section(ctx) {
+        page = page.section("AUTHORS", authors);
+    }
+    page
+}
+
+/// Renders each chapter of the book as a separate man page. If `chapters` is set in the
+/// configuration only the selected chapters are rendered, chapter indices refer to the
+/// positions in the configured `chapter-order`. The page configured as `index-page` follows
+/// the pages of the chapters. Use [`roff_to_string`](roff_to_string) to render the returned
+/// pages.
+pub fn mdbook_to_roff_chapters(ctx: &RenderContext) -> Result<Vec<Roff>, Error> {
+    let pages = mdbook_to_named_roff_chapters(ctx)?;
+    Ok(pages.into_iter().map(|(_, page)| page).collect())
+}
+
+/// Same as [`mdbook_to_roff_chapters`](mdbook_to_roff_chapters) but returns every page together
+/// with the name of the chapter it was rendered from.
+pub fn mdbook_to_named_roff_chapters(ctx: &RenderContext) -> Result<Vec<(String, Roff)>, Error> {
+    let cfg = ManOutputConfiguration::load(ctx)?;
+    render_chapter_pages(ctx, &configured_chapters(ctx, &cfg)?, &cfg)
+}
+
+/// The chapter a page was rendered from, passed to the closure of
+/// [`mdbook_to_roff_chapters_with`](mdbook_to_roff_chapters_with).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChapterMeta {
+    /// Name of the chapter.
+    pub name: String,
+    /// Number of the chapter in the summary like `[1, 2]` for `1.2.`, `None` for unnumbered
+    /// chapters.
+    pub number: Option<Vec<u32>>,
+    /// Path of the source file relative to the source directory, `None` for draft chapters.
+    pub path: Option<PathBuf>,
+}
+
+impl ChapterMeta {
+    fn of(chapter: &Chapter) -> Self {
+        Self {
+            name: chapter.name.clone(),
+            number: chapter.number.as_ref().map(|number| number.0.clone()),
+            path: chapter.path.clone(),
+        }
+    }
+}
+
+/// Same as [`mdbook_to_roff_chapters`](mdbook_to_roff_chapters) but passes the page of every
+/// chapter through `f` before returning it, for example to add sections of its own to every
+/// page. The index page is returned as it is.
+pub fn mdbook_to_roff_chapters_with<F>(ctx: &RenderContext, f: F) -> Result<Vec<Roff>, Error>
+where
+    F: Fn(&ChapterMeta, Roff) -> Roff,
+{
+    let cfg = ManOutputConfiguration::load(ctx)?;
+    let chapters = configured_chapters(ctx, &cfg)?;
+    let pages = render_chapter_pages(ctx, &chapters, &cfg)?;
+    let pages = pages
+        .into_iter()
+        .enumerate()
+        .map(|(i, (_, page))| match chapters.get(i) {
+            Some(chapter) => f(&ChapterMeta::of(chapter), page),
+            None => page,
+        });
+    Ok(pages.collect())
+}
+
+/// Returns whether none of the chapters that would be rendered has any content, like a book
+/// without chapters or with nothing but draft chapters.
+pub fn is_empty_book(ctx: &RenderContext) -> Result<bool, Error> {
+    let cfg = ManOutputConfiguration::load(ctx)?;
+    let chapters = if cfg.split_chapters {
+        configured_chapters(ctx, &cfg)?
+    } else {
+        book_chapters(ctx, &cfg)?
+    };
+    Ok(chapters.iter().all(|ch| ch.content.trim().is_empty()))
+}
+
+/// Returns the chapters rendered as separate pages, the ones selected by `chapters` if it is
+/// set.
+fn configured_chapters<'a>(
+    ctx: &'a RenderContext,
+    cfg: &ManOutputConfiguration,
+) -> Result<Vec<&'a Chapter>, Error> {
+    let chapters = book_chapters(ctx, cfg)?;
+    let chapters = match &cfg.chapters {
+        Some(selectors) => select_chapters(&chapters, selectors)?,
+        None => chapters,
+    };
+    let mut pages = Vec::with_capacity(chapters.len());
+    for ch in chapters {
+        if FrontMatter::of(ch)?.man_page != Some(false) {
+            pages.push(ch);
+        }
+    }
+    Ok(pages)
+}
+
+/// Returns the names of the files the pages returned by
+/// [`mdbook_to_roff_chapters`](mdbook_to_roff_chapters) are saved to, in the same order. A page
+/// whose file name is taken by an earlier page is saved to the name with `-2`, `-3` and so on
+/// appended to its stem, like `usage-2.man`.
+pub fn split_filenames(ctx: &RenderContext) -> Result<Vec<String>, Error> {
+    let cfg = ManOutputConfiguration::load(ctx)?;
+    let chapters = configured_chapters(ctx, &cfg)?;
+    let width = chapters.len().to_string().len();
+
+    let matters = chapters
+        .iter()
+        .map(|ch| FrontMatter::of(ch))
+        .collect::<Result<Vec<_>, _>>()?;
+    let filenames = chapters
+        .iter()
+        .zip(matters)
+        .enumerate()
+        .map(|(i, (ch, matter))| {
+            if let Some(filename) = matter.filename {
+                return filename;
+            }
+            let slug = config::slug(&ch.name);
+            if !cfg.number_filenames || slug.is_empty() {
+                return format!("chapter{}.man", i);
+            }
+            match &ch.number {
+                Some(number) => {
+                    let number = number
+                        .iter()
+                        .map(|n| format!("{:0width$}", n, width = width))
+                        .collect::<Vec<_>>()
+                        .join(".");
+                    format!("{}-{}.man", number, slug)
+                }
+                None => format!("{}.man", slug),
+            }
+        });
+    let mut filenames: Vec<_> = filenames.collect();
+    if let Some((name, _)) = index(&cfg) {
+        filenames.push(format!("{}.man", config::slug(&name)));
+    }
+    Ok(unique_filenames(filenames))
+}
+
+/// Renames every file name that is already taken by an earlier page by appending `-2`, `-3`
+/// and so on to its stem, so that no page overwrites another one.
+fn unique_filenames(filenames: Vec<String>) -> Vec<String> {
+    let mut unique: Vec<String> = Vec::with_capacity(filenames.len());
+    for filename in filenames {
+        if !unique.contains(&filename) {
+            unique.push(filename);
+            continue;
+        }
+        let (stem, extension) = match filename.rfind('.').filter(|&i| i > 0) {
+            Some(i) => filename.split_at(i),
+            None => (filename.as_str(), ""),
+        };
+        let renamed = (2..)
+            .map(|n| format!("{}-{}{}", stem, n, extension))
+            .find(|renamed| !unique.contains(renamed))
+            .unwrap_or_default();
+        eprintln!(
+            "mdbook-man: warning: more than one page is saved to `{}`, writing `{}` instead",
+            filename, renamed
+        );
+        unique.push(renamed);
+    }
+    unique
+}
+
+/// Returns the file name and content of every page configured in `aliases`, a single `.so`
+/// request including the page it stands for. An alias may not take one of the `filenames` of the
+/// rendered pages, so that it is checked before any page is written.
+pub fn alias_pages(
+    cfg: &ManOutputConfiguration,
+    filenames: &[String],
+) -> Result<Vec<(String, String)>, Error> {
+    let mut pages = Vec::with_capacity(cfg.aliases.len());
+    for (alias, target) in &cfg.aliases {
+        if filenames.contains(alias) {
+            return Err(Error::config(
+                format!("{}.aliases", ManOutputConfiguration::KEY),
+                format!("`{}` is the file of a rendered page", alias),
+            ));
+        }
+        pages.push((alias.clone(), format!(".so {}\n", target.trim())));
+    }
+    Ok(pages)
+}
+
+/// FNV-1a hash of the sources of a page. Unlike the hashers of the standard library it is the
+/// same with every release of Rust, so the manifest of an incremental build stays valid.
+#[derive(Clone)]
+struct SourceHash(u64);
+
+impl SourceHash {
+    fn new() -> Self {
+        SourceHash(0xcbf2_9ce4_8422_2325)
+    }
+
+    /// Adds `bytes` preceded by their length so that consecutive inputs can't run together.
+    fn add(&mut self, bytes: impl AsRef<[u8]>) {
+        let bytes = bytes.as_ref();
+        for byte in (bytes.len() as u64).to_le_bytes().iter().chain(bytes) {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+/// Returns `value` serialized as TOML, the same for equal values.
+fn serialized(value: &impl serde::Serialize) -> Result<String, Error> {
+    toml::Value::try_from(value)
+        .map(|value| value.to_string())
+        .map_err(|e| Error::config(ManOutputConfiguration::KEY, e.to_string()))
+}
+
+/// Returns a hash of the sources of every page returned by
+/// [`mdbook_to_roff_chapters`](mdbook_to_roff_chapters), in the same order. The hash of a page
+/// changes with the content of its chapter, the configuration and anything of the other chapters
+/// that ends up on the page, like their names, so a page whose hash is unchanged since an earlier
+/// build renders the same. The index page also changes with the content of the first chapter its
+/// `NAME` section is taken from. The hashes are only comparable between builds with the same
+/// version of mdbook-man.
+pub fn split_page_hashes(ctx: &RenderContext) -> Result<Vec<u64>, Error> {
+    let cfg = ManOutputConfiguration::load(ctx)?;
+    let chapters = configured_chapters(ctx, &cfg)?;
+
+    let mut shared = SourceHash::new();
+    shared.add(env!("CARGO_PKG_VERSION"));
+    shared.add(&ctx.version);
+    shared.add(serialized(&cfg)?);
+    shared.add(serialized(&ctx.config.book)?);
+    for ch in all_chapters(&ctx.book) {
+        shared.add(&ch.name);
+        shared.add(
+            ch.number
+                .as_ref()
+                .map(ToString::to_string)
+                .unwrap_or_default(),
+        );
+        let path = ch.path.as_deref().map(|path| path.to_string_lossy());
+        shared.add(path.unwrap_or_default().as_bytes());
+        shared.add(frontmatter::split(&ch.content).0.unwrap_or_default());
+    }
+
+    let page_hash = |ch: &Chapter| {
+        let mut hash = shared.clone();
+        hash.add(&ch.name);
+        hash.add(&ch.content);
+        hash.0
+    };
+    let mut hashes: Vec<_> = chapters.iter().map(|ch| page_hash(ch)).collect();
+    if index(&cfg).is_some() {
+        // the NAME section of the index falls back to the first sentence of the first chapter
+        let mut hash = shared.clone();
+        hash.add(chapters.first().map_or("", |ch| ch.content.as_str()));
+        hashes.push(hash.0);
+    }
+    Ok(hashes)
+}
 
-    pages
+/// Renders only the chapters matching `selectors` as separate man pages, ignoring the `chapters`
+/// set in the configuration. Returns an error if any of the chapters doesn't exist.
+pub fn mdbook_to_roff_selected_chapters(
+    ctx: &RenderContext,
+    selectors: &[ChapterSelector],
+) -> Result<Vec<Roff>, Error> {
+    let cfg = ManOutputConfiguration::load(ctx)?;
+    let chapters = select_chapters(&book_chapters(ctx, &cfg)?, selectors)?;
+    let pages = render_chapter_pages(ctx, &chapters, &cfg)?;
+    Ok(pages.into_iter().map(|(_, page)| page).collect())
 }
@@ -0,0 +1,141 @@
+//! Links between the chapters of a book.
+//!
+//! Relative links to the markdown source of another chapter are meaningless in a man page, so
+//! they are rendered as a reference to the section or page the chapter ends up in instead.
+
+use mdbook::book::Chapter;
+use roffman::{IntoRoffNode, RoffNode, Roffable};
+
+use std::{
+    collections::HashMap,
+    path::{Component, Path, PathBuf},
+};
+
+/// How the chapters linked to are referenced.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) enum ReferenceStyle {
+    /// Chapters are sections of the same page.
+    #[default]
+    Section,
+    /// Chapters are separate pages in the section of the manual.
+    Page(u8),
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct ChapterLinks {
+    style: ReferenceStyle,
+    /// Names the chapters are referenced by, keyed by the path of their source file.
+    names: HashMap<PathBuf, String>,
+    /// Sections of the pages of chapters that differ from the one of the reference style.
+    sections: HashMap<PathBuf, u8>,
+    /// Directory of the chapter being rendered that relative links are resolved from.
+    dir: PathBuf,
+    /// Directory holding the sources of the book.
+    source_dir: PathBuf,
+}
+
+impl ChapterLinks {
+    /// Creates the links to `chapters` referenced by the name returned from `name`.
+    pub(crate) fn new<'a>(
+        chapters: impl IntoIterator<Item = &'a Chapter>,
+        style: ReferenceStyle,
+        name: impl Fn(&Chapter) -> String,
+    ) -> Self {
+        let names = chapters
+            .into_iter()
+            .filter_map(|ch| Some((normalize(ch.path.as_deref()?), name(ch))))
+            .collect();
+        Self {
+            style,
+            names,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the sections of the manual the pages of `chapters` are referenced in to the ones
+    /// returned from `section`, chapters it returns `None` for stay in the section of the style.
+    pub(crate) fn sections<'a>(
+        mut self,
+        chapters: impl IntoIterator<Item = &'a Chapter>,
+        section: impl Fn(&Chapter) -> Option<u8>,
+    ) -> Self {
+        self.sections = chapters
+            .into_iter()
+            .filter_map(|ch| Some((normalize(ch.path.as_deref()?), section(ch)?)))
+            .collect();
+        self
+    }
+
+    /// Resolves links to local files relative to the chapters in `source_dir`.
+    pub(crate) fn source_dir(mut self, source_dir: PathBuf) -> Self {
+        self.source_dir = source_dir;
+        self
+    }
+
+    /// Returns the path of the existing local file `url` links to.
+    pub(crate) fn local_file(&self, url: &str) -> Option<PathBuf> {
+        let path = url.split(['#', '?']).next().unwrap_or_default();
+        if path.is_empty() || url.contains(':') {
+            return None;
+        }
+        let path = normalize(&self.source_dir.join(&self.dir).join(path));
+        if path.is_file() {
+            Some(path)
+        } else {
+            None
+        }
+    }
+
+    /// Resolves following links relative to the source file of `chapter`.
+    pub(crate) fn enter(&mut self, chapter: &Chapter) {
+        self.dir = chapter
+            .path
+            .as_deref()
+            .and_then(Path::parent)
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+    }
+
+    /// Returns the nodes referencing the chapter `url` links to. Returns `None` if `url` is not a
+    /// relative link to a markdown file, links to chapters that are not rendered have an empty
+    /// reference.
+    pub(crate) fn reference(&self, url: &str) -> Option<Vec<RoffNode>> {
+        let path = url.split(['#', '?']).next().unwrap_or_default();
+        if url.contains("://") || url.starts_with('/') || !path.ends_with(".md") {
+            return None;
+        }
+        let path = normalize(&self.dir.join(path));
+        let name = match self.names.get(&path) {
+            Some(name) => name,
+            None => return Some(vec![]),
+        };
+
+        Some(match self.style {
+            ReferenceStyle::Section => vec![
+                " (see ".into_roff(),
+                name.roff().italic().into_roff(),
+                ")".into_roff(),
+            ],
+            ReferenceStyle::Page(section) => vec![
+                " (see ".into_roff(),
+                name.roff().bold().into_roff(),
+                format!("({}))", self.sections.get(&path).unwrap_or(&section)).into_roff(),
+            ],
+        })
+    }
+}
+
+/// Removes all `.` and `..` components from `path`.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            component => out.push(component),
+        }
+    }
+    out
+}
@@ -0,0 +1,128 @@
+//! Checks of rendered pages that catch malformed roff before it is written.
+
+use crate::raw;
+
+use std::fmt;
+
+/// Pairs of requests that start and end a block.
+const BLOCKS: &[(&str, &str)] = &[
+    (".RS", ".RE"),
+    (".TS", ".TE"),
+    (".EX", ".EE"),
+    (".nf", ".fi"),
+    (".Bd", ".Ed"),
+    (".Bl", ".El"),
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// A problem found in a rendered page.
+pub struct LintIssue {
+    /// The one based number of the offending line.
+    pub line: usize,
+    /// Title of the section the line is part of, if any.
+    pub section: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.section {
+            Some(section) => write!(
+                f,
+                "line {} in section `{}`: {}",
+                self.line, section, self.message
+            ),
+            None => write!(f, "line {}: {}", self.line, self.message),
+        }
+    }
+}
+
+/// Returns the name of the request on `line`, if it is a control line.
+fn request_name(line: &str) -> Option<&str> {
+    let rest = line.strip_prefix('.')?;
+    Some(rest.split(char::is_whitespace).next().unwrap_or_default())
+}
+
+/// Checks the `rendered` page for a missing header, unbalanced blocks and stray control
+/// characters. Returns all problems found, the page is well formed if there are none.
+pub fn lint_page(rendered: &str) -> Vec<LintIssue> {
+    let mut issues = vec![];
+    let mut section: Option<String> = None;
+    let mut open: Vec<(&str, usize, Option<String>)> = vec![];
+    let mut header = false;
+
+    let mut issue = |line: usize, section: &Option<String>, message: String| {
+        issues.push(LintIssue {
+            line,
+            section: section.clone(),
+            message,
+        })
+    };
+
+    for (i, line) in rendered.lines().enumerate() {
+        let number = i + 1;
+        if line.starts_with(".\\\"") || line.starts_with("'\\\"") {
+            continue;
+        }
+
+        if let Some(c) = line
+            .chars()
+            .find(|c| (c.is_control() && *c != '\t') || raw::MARKERS.contains(c))
+        {
+            issue(number, &section, format!("stray control character {:?}", c));
+        }
+        if line.starts_with('\'') {
+            issue(
+                number,
+                &section,
+                "text line starts with the control character `'`".into(),
+            );
+        }
+
+        let name = match request_name(line) {
+            Some(name) => name,
+            None => continue,
+        };
+        if !header {
+            if name != "TH" && name != "Dd" {
+                issue(
+                    number,
+                    &section,
+                    "page doesn't start with a `.TH` header".into(),
+                );
+            }
+            header = true;
+        }
+        if matches!(name, "SH" | "Sh") {
+            let title = line[3..].trim().trim_matches('"');
+            section = Some(title.to_string());
+        }
+
+        let request = &line[..name.len() + 1];
+        if let Some((start, _)) = BLOCKS.iter().find(|(start, _)| *start == request) {
+            open.push((start, number, section.clone()));
+        } else if let Some((start, end)) = BLOCKS.iter().find(|(_, end)| *end == request) {
+            match open.iter().rposition(|(open, _, _)| open == start) {
+                Some(pos) => {
+                    for (unclosed, line, section) in open.drain(pos..).skip(1) {
+                        issue(line, &section, format!("`{}` is never closed", unclosed));
+                    }
+                }
+                None => issue(
+                    number,
+                    &section,
+                    format!("`{}` without a preceding `{}`", end, start),
+                ),
+            }
+        }
+    }
+
+    if !header {
+        issue(1, &None, "page doesn't start with a `.TH` header".into());
+    }
+    for (unclosed, line, section) in open {
+        issue(line, &section, format!("`{}` is never closed", unclosed));
+    }
+
+    issues
+}
@@ -1,70 +1,164 @@
-// src/main.rs
 extern crate mdbook;
 
 use mdbook::renderer::RenderContext;
-use serde::{Deserialize, Serialize};
-
-use std::{fs, io, path::PathBuf};
-
-#[derive(Debug, Default, Serialize, Deserialize)]
-#[serde(default, rename_all = "kebab-case")]
-struct ManOutputConfiguration {
-    /// If specified the pages will be saved as files rather than printed to stdout.
-    pub output_dir: Option<PathBuf>,
-    #[serde(default)]
-    /// Wether to split the book into separate files per chapter or render one man page with all chapters.
-    pub split_chapters: bool,
-    /// Override the name of the output file if `output_dir` is also specified.
-    pub filename: Option<String>,
+use mdbook_man::{EmptyBook, Error, ManOutputConfiguration};
+
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Write},
+    path::Path,
+    process,
+};
+
+fn print_page(page: &str, cfg: &ManOutputConfiguration) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    stdout.write_all(page.as_bytes())?;
+    if !cfg.no_trailing_newline {
+        stdout.write_all(b"\n")?;
+    }
+    stdout.flush()
 }
 
-impl ManOutputConfiguration {
-    fn load(ctx: &RenderContext) -> Self {
-        ctx.config
-            .get_deserialized_opt("output.man")
-            .ok()
-            .flatten()
-            .unwrap_or_default()
+/// Prints the comment separating the pages of split chapters on stdout, naming the `filename`
+/// of the page following it.
+fn print_separator(filename: &str, cfg: &ManOutputConfiguration) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    if cfg.no_trailing_newline {
+        stdout.write_all(b"\n")?;
     }
+    writeln!(stdout, ".\\\" mdbook-man page: {}", filename)
 }
 
-fn main() {
+/// Name of the file in the output directory recording the hash of every page written by an
+/// incremental build.
+const MANIFEST: &str = ".mdbook-man-manifest";
+
+/// Reads the manifest in `dir` as a map from file names to the hashes of their pages. A missing
+/// or malformed manifest is read as empty so that every page is written.
+fn read_manifest(dir: &Path) -> HashMap<String, u64> {
+    let manifest = fs::read_to_string(dir.join(MANIFEST)).unwrap_or_default();
+    manifest
+        .lines()
+        .filter_map(|line| {
+            let (hash, filename) = line.split_once(' ')?;
+            let hash = u64::from_str_radix(hash, 16).ok()?;
+            Some((filename.to_string(), hash))
+        })
+        .collect()
+}
+
+/// Writes the split pages into `dir` whose hash differs from the one in the manifest of the
+/// previous build and removes the files of pages that are gone.
+fn write_changed_pages(ctx: &RenderContext, dir: &Path, filenames: &[String]) -> Result<(), Error> {
+    let hashes = mdbook_man::split_page_hashes(ctx)?;
+    let mut previous = read_manifest(dir);
+
+    let changed: Vec<_> = filenames
+        .iter()
+        .zip(&hashes)
+        .map(|(filename, hash)| {
+            previous.remove(filename) != Some(*hash) || !dir.join(filename).exists()
+        })
+        .collect();
+
+    if !dir.exists() {
+        fs::create_dir_all(dir)?;
+    }
+    // the pages are rendered together as they refer to each other
+    if changed.contains(&true) {
+        let pages = mdbook_man::mdbook_to_roff_chapters(ctx)?;
+        for (i, page) in pages.iter().enumerate().filter(|(i, _)| changed[*i]) {
+            fs::write(
+                dir.join(&filenames[i]),
+                mdbook_man::page_to_string(ctx, page)?,
+            )?;
+        }
+    }
+    // whatever is left of the previous manifest belongs to chapters that were removed
+    for filename in previous.keys() {
+        match fs::remove_file(dir.join(filename)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+    }
+
+    let manifest: String = filenames
+        .iter()
+        .zip(&hashes)
+        .map(|(filename, hash)| format!("{:016x} {}\n", hash, filename))
+        .collect();
+    fs::write(dir.join(MANIFEST), manifest)?;
+    Ok(())
+}
+
+fn run() -> Result<(), Error> {
     let mut stdin = io::stdin();
-    let ctx = RenderContext::from_json(&mut stdin).unwrap();
-    let cfg = ManOutputConfiguration::load(&ctx);
+    let ctx = RenderContext::from_json(&mut stdin).map_err(|e| Error::Context(e.to_string()))?;
+    let cfg = ManOutputConfiguration::load(&ctx)?;
+
+    let empty = mdbook_man::is_empty_book(&ctx)?;
+    if empty {
+        eprintln!("mdbook-man: warning: the book has no chapters with content");
+        if cfg.empty_book == EmptyBook::Skip {
+            return Ok(());
+        }
+    }
 
-    if !cfg.split_chapters {
-        let page = mdbook_man::mdbook_to_roff(&ctx);
+    // the split pages of an empty book would not even have a header
+    let combined = !cfg.split_chapters || empty;
+    let filenames = if combined {
+        vec![cfg.combined_filename(ctx.config.book.title.as_deref())]
+    } else {
+        mdbook_man::split_filenames(&ctx)?
+    };
+    let aliases = mdbook_man::alias_pages(&cfg, &filenames)?;
 
-        let page = page.to_string().unwrap();
+    if combined {
+        let page = mdbook_man::mdbook_to_string(&ctx)?;
 
-        if let Some(path) = cfg.output_dir {
+        if let Some(path) = &cfg.output_dir {
             if !path.exists() {
-                fs::create_dir_all(&path).unwrap();
+                fs::create_dir_all(path)?;
             }
-            let filename = if let Some(filename) = &cfg.filename {
-                filename
-            } else {
-                "book.man"
-            };
-            fs::write(path.join(filename), page).unwrap()
+            fs::write(path.join(&filenames[0]), page)?
         } else {
-            println!("{}", page)
+            print_page(&page, &cfg)?
         }
+    } else if let (true, Some(path)) = (cfg.incremental, &cfg.output_dir) {
+        write_changed_pages(&ctx, path, &filenames)?
     } else {
-        let pages = mdbook_man::mdbook_to_roff_chapters(&ctx);
+        let pages = mdbook_man::mdbook_to_roff_chapters(&ctx)?;
 
-        for (i, page) in pages.iter().enumerate() {
-            let page = page.to_string().unwrap();
+        for (i, (page, filename)) in pages.iter().zip(&filenames).enumerate() {
+            let page = mdbook_man::page_to_string(&ctx, page)?;
 
             if let Some(path) = &cfg.output_dir {
                 if !path.exists() {
-                    fs::create_dir_all(&path).unwrap();
+                    fs::create_dir_all(path)?;
                 }
-                fs::write(path.join(format!("chapter{}.man", i)), page).unwrap()
+                fs::write(path.join(filename), page)?
             } else {
-                println!("{}", page)
+                if i > 0 {
+                    print_separator(filename, &cfg)?
+                }
+                print_page(&page, &cfg)?
             }
         }
     }
+
+    if let Some(path) = &cfg.output_dir {
+        for (filename, page) in aliases {
+            fs::write(path.join(filename), page)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("mdbook-man: {}", e);
+        process::exit(1);
+    }
 }
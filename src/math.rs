@@ -0,0 +1,187 @@
+//! Math written as `$inline$` or `$$display$$` LaTeX, kept as its source text.
+//!
+//! comrak has no math extension, so the math is rewritten before parsing into elements whose
+//! content comrak leaves alone. Display math becomes a fenced `math` code block and inline math
+//! a code span marked with [`INLINE_MARKER`]. Code and HTML blocks are left as they are.
+
+use comrak::{
+    nodes::{AstNode, NodeValue},
+    parse_document, Arena, ComrakOptions,
+};
+
+/// Start of the code spans holding inline math, one of the noncharacters of
+/// [`raw::MARKERS`](crate::raw::MARKERS) that text doesn't contain.
+pub(crate) const INLINE_MARKER: &str = "\u{FDD2}";
+
+/// Returns the source of the inline math held by the code span with the `literal`, if it is one.
+pub(crate) fn inline_source(literal: &[u8]) -> Option<&[u8]> {
+    literal.strip_prefix(INLINE_MARKER.as_bytes())
+}
+
+/// Language of the code blocks holding display math.
+pub(crate) const LANGUAGE: &str = "math";
+
+/// Returns the length of the run of `c` at the start of `text`.
+fn run_length(text: &str, c: char) -> usize {
+    text.chars().take_while(|&ch| ch == c).count()
+}
+
+/// Returns the longest run of backticks in `text`.
+fn run_length_max(text: &str) -> usize {
+    text.split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or_default()
+}
+
+/// Returns the offset of the `$` closing inline math that starts after the opening `$` of
+/// `rest`. Math can't start or end with a space and the closing `$` can't be followed by a digit,
+/// so that amounts like `$5 and $6` stay text.
+fn closing_dollar(rest: &str) -> Option<usize> {
+    if rest.starts_with(char::is_whitespace) || rest.starts_with('$') {
+        return None;
+    }
+    let mut escaped = false;
+    for (i, c) in rest.char_indices() {
+        match c {
+            // code spans end the text the math could be part of
+            '\n' | '`' => return None,
+            '\\' => escaped = !escaped,
+            '$' if !escaped => {
+                let before = rest[..i].chars().next_back()?;
+                let after = rest[i + 1..].chars().next();
+                return (!before.is_whitespace() && !after.is_some_and(|c| c.is_ascii_digit()))
+                    .then_some(i);
+            }
+            _ => escaped = false,
+        }
+    }
+    None
+}
+
+/// Rewrites the inline math of a single `line` into marked code spans, leaving existing code
+/// spans as they are.
+fn inline(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(i) = rest.find(['`', '$', '\\', '<']) {
+        out.push_str(&rest[..i]);
+        rest = &rest[i..];
+        if rest.starts_with('<') {
+            // inline HTML and autolinks are copied as they are
+            let tag = rest[1..].starts_with(|c: char| c.is_ascii_alphabetic() || "/!?".contains(c));
+            let len = rest.find('>').filter(|_| tag).map_or(1, |end| end + 1);
+            out.push_str(&rest[..len]);
+            rest = &rest[len..];
+        } else if rest.starts_with('\\') {
+            // an escaped dollar sign or backtick is text
+            let len = rest.chars().nth(1).map_or(1, |c| 1 + c.len_utf8());
+            out.push_str(&rest[..len]);
+            rest = &rest[len..];
+        } else if rest.starts_with('`') {
+            let fence = "`".repeat(run_length(rest, '`'));
+            let end = rest[fence.len()..]
+                .find(&fence)
+                .map_or(fence.len(), |end| 2 * fence.len() + end);
+            out.push_str(&rest[..end]);
+            rest = &rest[end..];
+        } else {
+            match closing_dollar(&rest[1..]) {
+                Some(end) => {
+                    let source = &rest[1..end + 1];
+                    let fence = "`".repeat(run_length_max(source) + 1);
+                    out.push_str(&format!("{}{}{}{}", fence, INLINE_MARKER, source, fence));
+                    rest = &rest[end + 2..];
+                }
+                None => {
+                    out.push('$');
+                    rest = &rest[1..];
+                }
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Returns whether each line of `text` is part of a code or HTML block when parsed with
+/// `options`, including the blocks nested in lists and block quotes.
+fn block_lines(text: &str, options: &ComrakOptions) -> Vec<bool> {
+    let arena = Arena::new();
+    let root: &AstNode = parse_document(&arena, text, options);
+    let mut lines = vec![false; text.lines().count() + 1];
+    for node in root.descendants() {
+        let ast = node.data.borrow();
+        let line_count = |literal: &[u8]| literal.iter().filter(|b| **b == b'\n').count().max(1);
+        let count = match &ast.value {
+            // the fences are lines of the block too
+            NodeValue::CodeBlock(block) if block.fenced => line_count(&block.literal) + 2,
+            NodeValue::CodeBlock(block) => line_count(&block.literal),
+            NodeValue::HtmlBlock(block) => line_count(&block.literal),
+            _ => continue,
+        };
+        let start = (ast.start_line as usize).saturating_sub(1);
+        for line in lines.iter_mut().skip(start).take(count) {
+            *line = true;
+        }
+    }
+    lines
+}
+
+/// Rewrites the math of the markdown `text` parsed with `options`. Lines of code and HTML blocks
+/// are left as they are.
+pub(crate) fn preprocess(text: &str, options: &ComrakOptions) -> String {
+    let blocks = block_lines(text, options);
+    let mut out = String::with_capacity(text.len());
+    let mut fence: Option<&str> = None;
+    let mut display = false;
+    for (number, line) in text.split_inclusive('\n').enumerate() {
+        if !display && blocks.get(number).copied().unwrap_or_default() {
+            out.push_str(line);
+            continue;
+        }
+        let trimmed = line.trim();
+        let content = line.trim_end_matches(['\n', '\r']);
+        let newline = &line[content.len()..];
+        if display {
+            if trimmed == "$$" {
+                display = false;
+                out.push_str("```");
+                out.push_str(newline);
+            } else {
+                out.push_str(line);
+            }
+            continue;
+        }
+        match fence {
+            Some(marker) if trimmed.starts_with(marker) => fence = None,
+            Some(_) => {}
+            None if trimmed.starts_with("```") => fence = Some("```"),
+            None if trimmed.starts_with("~~~") => fence = Some("~~~"),
+            None if trimmed == "$$" => {
+                display = true;
+                out.push_str(&format!("```{}\n", LANGUAGE));
+                continue;
+            }
+            None => {
+                let single = trimmed
+                    .strip_prefix("$$")
+                    .and_then(|rest| rest.strip_suffix("$$"))
+                    .filter(|source| !source.trim().is_empty());
+                match single {
+                    Some(source) => {
+                        let source = source.trim();
+                        out.push_str(&format!("```{}\n{}\n```{}", LANGUAGE, source, newline));
+                    }
+                    None => out.push_str(&format!("{}{}", inline(content), newline)),
+                }
+                continue;
+            }
+        }
+        out.push_str(line);
+    }
+    if display {
+        out.push_str("\n```\n");
+    }
+    out
+}
@@ -0,0 +1,245 @@
+//! Conversion of the rendered `man` pages to the semantic `mdoc` macro package.
+//!
+//! roffman only knows about the `man` macros, so pages are rendered as usual and the resulting
+//! requests are translated line by line afterwards. Font escapes and low level roff requests are
+//! understood by both packages and are kept as they are.
+
+/// Block macros that are open at the current line of the page.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Block {
+    /// An indented display started by `.RS`.
+    Display,
+    /// A tagged list started by the first `.IP` or `.TP` of a sequence.
+    List,
+    /// A literal display started by `.EX`.
+    Literal,
+}
+
+impl Block {
+    fn end(self) -> &'static str {
+        match self {
+            Block::List => ".El",
+            Block::Display | Block::Literal => ".Ed",
+        }
+    }
+}
+
+#[derive(Default)]
+struct Converter {
+    out: Vec<String>,
+    blocks: Vec<Block>,
+    /// Set after a `.TP` until the line with its tag is read.
+    tag_pending: bool,
+    /// Set between `.PD 0` and `.PD`, paragraphs only end the lists before them.
+    tight: bool,
+}
+
+impl Converter {
+    fn push(&mut self, line: impl Into<String>) {
+        self.out.push(line.into());
+    }
+
+    fn open(&mut self, block: Block, line: &str) {
+        self.blocks.push(block);
+        self.push(line);
+    }
+
+    /// Closes all lists on top of the open blocks.
+    fn close_lists(&mut self) {
+        while self.blocks.last() == Some(&Block::List) {
+            self.blocks.pop();
+            self.push(".El");
+        }
+    }
+
+    /// Closes all blocks up to and including the innermost `block`.
+    fn close_until(&mut self, block: Block) {
+        while let Some(open) = self.blocks.pop() {
+            self.push(open.end());
+            if open == block {
+                break;
+            }
+        }
+    }
+
+    fn close_all(&mut self) {
+        while let Some(open) = self.blocks.pop() {
+            self.push(open.end());
+        }
+    }
+
+    /// Starts an item of a tagged list, opening the list first if needed.
+    fn item(&mut self, tag: &str) {
+        if self.blocks.last() != Some(&Block::List) {
+            self.open(Block::List, ".Bl -tag -width Ds");
+        }
+        if tag.is_empty() {
+            self.push(".It");
+        } else {
+            self.push(format!(".It {}", tag));
+        }
+    }
+
+    /// Returns true if a paragraph break would directly follow the start of a section or item.
+    fn at_block_start(&self) -> bool {
+        match self.out.last() {
+            Some(line) => {
+                line.starts_with(".Sh")
+                    || line.starts_with(".Ss")
+                    || line.starts_with(".It")
+                    || line.starts_with(".Bd")
+                    || line.starts_with(".Pp")
+            }
+            None => true,
+        }
+    }
+}
+
+/// Splits a request line into its name and the rest of its arguments.
+fn request(line: &str) -> Option<(&str, &str)> {
+    let line = line.strip_prefix('.')?;
+    let (name, args) = line.split_once(' ').unwrap_or((line, ""));
+    Some((name, args.trim()))
+}
+
+/// Splits the arguments of a request honoring double quotes.
+fn arguments(args: &str) -> Vec<String> {
+    let mut out = vec![];
+    let mut rest = args.trim_start();
+    while !rest.is_empty() {
+        if let Some(quoted) = rest.strip_prefix('"') {
+            let end = quoted.find('"').unwrap_or(quoted.len());
+            out.push(quoted[..end].to_string());
+            rest = quoted.get(end + 1..).unwrap_or_default();
+        } else {
+            let end = rest.find(' ').unwrap_or(rest.len());
+            out.push(rest[..end].to_string());
+            rest = &rest[end..];
+        }
+        rest = rest.trim_start();
+    }
+    out
+}
+
+/// Translates the `.TH` header to the `mdoc` prologue followed by the `NAME` section.
+fn prologue(args: &str, description: &str, out: &mut Converter) {
+    let args = arguments(args);
+    let name = args.first().map(String::as_str).unwrap_or_default();
+    let section = args.get(1).map(String::as_str).unwrap_or("7");
+    let title = name.split_whitespace().collect::<Vec<_>>().join("-");
+
+    let field = |i: usize| {
+        args.get(i)
+            .map(String::as_str)
+            .filter(|field| !field.is_empty())
+    };
+
+    out.push(format!(".Dd {}", field(2).unwrap_or("$Mdocdate$")));
+    out.push(format!(".Dt {} {}", title.to_uppercase(), section));
+    match field(3) {
+        Some(source) => out.push(format!(".Os {}", source)),
+        None => out.push(".Os"),
+    }
+    out.push(".Sh NAME");
+    out.push(format!(".Nm {}", title));
+    out.push(format!(".Nd {}", description.replace('\n', " ")));
+}
+
+/// Returns the summary given in the `NAME` section of the page, if it has one.
+fn name_summary(rendered: &str) -> Option<&str> {
+    let mut lines = rendered
+        .lines()
+        .skip_while(|line| *line != ".SH NAME")
+        .skip(1);
+    let line = lines.find(|line| !line.trim().is_empty() && !line.starts_with('.'))?;
+    Some(line.split_once(" \\- ").map_or("", |(_, summary)| summary))
+}
+
+/// Converts a page rendered with the `man` macros to the `mdoc` macros. The `NAME` section is
+/// generated from the name in the header and the summary of the page's own `NAME` section or
+/// else the `description`.
+pub(crate) fn from_man(rendered: &str, description: &str) -> String {
+    let mut out = Converter::default();
+    let mut in_url = false;
+    let mut in_name = false;
+    let description = name_summary(rendered).unwrap_or(description);
+
+    for line in rendered.lines() {
+        if in_name {
+            // the requests of the preamble are the only ones worth keeping
+            match request(line) {
+                Some(("SH", _)) => in_name = false,
+                Some(("P", _)) | None => continue,
+                Some(_) => {
+                    out.push(line);
+                    continue;
+                }
+            }
+        }
+        if out.blocks.last() == Some(&Block::Literal) && line != ".EE" {
+            out.push(line);
+            continue;
+        }
+        if std::mem::take(&mut out.tag_pending) {
+            out.item(line);
+            continue;
+        }
+
+        let (name, args) = match request(line) {
+            Some(request) => request,
+            None => {
+                // blank lines have a meaning of their own outside of literal displays
+                if !line.trim().is_empty() && !in_url {
+                    out.push(line);
+                }
+                continue;
+            }
+        };
+        match name {
+            "TH" => prologue(args, description, &mut out),
+            "SH" if args == "NAME" => in_name = true,
+            "SH" | "SS" => {
+                out.close_all();
+                let macro_name = if name == "SH" { ".Sh" } else { ".Ss" };
+                out.push(format!("{} {}", macro_name, args));
+            }
+            "P" | "PP" | "LP" => {
+                out.close_lists();
+                if !out.tight && !out.at_block_start() {
+                    out.push(".Pp");
+                }
+            }
+            // the spacing of paragraphs is up to mdoc
+            "PD" => out.tight = args == "0",
+            "br" if out.at_block_start() => {}
+            "IP" => {
+                let tag = arguments(args).into_iter().next().unwrap_or_default();
+                out.item(&tag);
+            }
+            "TP" => out.tag_pending = true,
+            "RS" => out.open(Block::Display, ".Bd -ragged -offset indent"),
+            "RE" => out.close_until(Block::Display),
+            "EX" => out.open(Block::Literal, ".Bd -literal"),
+            "EE" => {
+                // roffman ends examples that follow inline text with an empty line
+                while out.out.last().is_some_and(|line| line.is_empty()) {
+                    out.out.pop();
+                }
+                out.close_until(Block::Literal);
+            }
+            "UR" => {
+                out.push(format!(".Lk {}", args));
+                in_url = true;
+            }
+            "MT" => {
+                out.push(format!(".Mt {}", args));
+                in_url = true;
+            }
+            "UE" | "ME" => in_url = false,
+            _ => out.push(line),
+        }
+    }
+    out.close_all();
+
+    out.out.join("\n")
+}
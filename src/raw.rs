@@ -0,0 +1,63 @@
+//! Support for roff requests that roffman has no nodes for.
+//!
+//! roffman escapes all of the text it renders, so raw requests are stored in text nodes wrapped
+//! in marker characters and put back in place by [`expand`](expand) once the page is rendered.
+
+use roffman::RoffNode;
+
+use std::{borrow::Cow, ops::RangeInclusive};
+
+/// Noncharacters reserved for the markers of this crate, they never stand for text.
+pub(crate) const MARKERS: RangeInclusive<char> = '\u{FDD0}'..='\u{FDEF}';
+
+const START: char = '\u{FDD0}';
+const END: char = '\u{FDD1}';
+
+/// Replaces the characters of [`MARKERS`](MARKERS) in the source `text` with U+FFFD, so that
+/// they can't be mistaken for markers.
+pub(crate) fn sanitize(text: Cow<'_, str>) -> Cow<'_, str> {
+    if text.chars().any(|c| MARKERS.contains(&c)) {
+        text.chars()
+            .map(|c| if MARKERS.contains(&c) { '\u{FFFD}' } else { c })
+            .collect::<String>()
+            .into()
+    } else {
+        text
+    }
+}
+
+/// Creates a node with `request` that will be rendered verbatim on a line of its own.
+pub(crate) fn request(request: impl AsRef<str>) -> RoffNode {
+    RoffNode::text(format!("{}{}{}", START, request.as_ref(), END))
+}
+
+/// Reverts the escaping roffman applies to text nodes.
+fn unescape(text: &str) -> String {
+    text.replace("\\.", ".").replace("\\-", "-")
+}
+
+/// Replaces all markers created with [`request`](request) in the `rendered` roff with the raw
+/// requests they hold.
+pub(crate) fn expand(rendered: &str) -> String {
+    let mut out = String::with_capacity(rendered.len());
+    let mut rest = rendered;
+
+    while let Some(start) = rest.find(START) {
+        out.push_str(&rest[..start]);
+        rest = &rest[start + START.len_utf8()..];
+        let end = rest.find(END).unwrap_or(rest.len());
+
+        if !out.is_empty() && !out.ends_with('\n') {
+            out.push('\n');
+        }
+        out.push_str(&unescape(&rest[..end]));
+
+        rest = rest.get(end + END.len_utf8()..).unwrap_or_default();
+        if !rest.starts_with('\n') && !out.ends_with('\n') {
+            out.push('\n');
+        }
+    }
+    out.push_str(rest);
+
+    out
+}
@@ -0,0 +1,150 @@
+//! Rendering of GFM tables, either with the `tbl` preprocessor or as plain aligned text.
+
+use crate::{escape, raw, text_content};
+
+use comrak::nodes::{AstNode, NodeValue, TableAlignment};
+use roffman::{IntoRoffNode, RoffNode};
+
+/// Line the page starts with when any of its tables has to be run through `tbl`.
+pub(crate) const PREPROCESSOR_LINE: &str = "'\\\" t";
+
+/// A rendered row of a table.
+#[derive(Debug, Default)]
+pub(crate) struct Row {
+    pub(crate) header: bool,
+    pub(crate) cells: Vec<Vec<RoffNode>>,
+}
+
+fn alignment_key(alignment: Option<&TableAlignment>) -> char {
+    match alignment {
+        Some(TableAlignment::Center) => 'c',
+        Some(TableAlignment::Right) => 'r',
+        _ => 'l',
+    }
+}
+
+/// Returns the number of columns of the table, rows with more cells than the table has columns
+/// widen it.
+fn column_count(alignments: &[TableAlignment], rows: impl Iterator<Item = usize>) -> usize {
+    rows.fold(alignments.len(), usize::max).max(1)
+}
+
+/// Renders the `rows` of a table as a `tbl` table. Rows with fewer cells than the table has
+/// columns are padded with empty cells. A header row without any content is left out.
+pub(crate) fn tbl(alignments: &[TableAlignment], rows: Vec<Row>) -> Vec<RoffNode> {
+    let columns = column_count(alignments, rows.iter().map(|row| row.cells.len()));
+    let header = rows
+        .first()
+        .is_some_and(|row| row.header && row.cells.iter().any(|cell| !cell.is_empty()));
+
+    let format = |modifier: &str| {
+        (0..columns)
+            .map(|i| format!("{}{}", alignment_key(alignments.get(i)), modifier))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let mut spec = String::from(".TS\ntab(\t);\n");
+    if header {
+        spec.push_str(&format("b"));
+        spec.push('\n');
+    }
+    spec.push_str(&format(""));
+    spec.push('.');
+
+    let mut nodes = vec![raw::request(spec)];
+    let mut first = true;
+    for row in rows {
+        if row.header && !header {
+            continue;
+        }
+        if !first {
+            nodes.push("\n".into_roff());
+        }
+        first = false;
+
+        // a cell starting with a control character would turn the row into a request
+        nodes.push("\\&".into_roff());
+        let mut cells = row.cells.into_iter();
+        for i in 0..columns {
+            if i > 0 {
+                nodes.push("\t".into_roff());
+            }
+            nodes.extend(cells.next().unwrap_or_default());
+        }
+        if row.header {
+            nodes.push(raw::request("_"));
+            first = true;
+        }
+    }
+    nodes.push(raw::request(".TE"));
+    nodes
+}
+
+/// Renders the `table` as lines of text with the columns aligned by padding them with spaces.
+/// Inline formatting of the cells is dropped.
+pub(crate) fn plain<'a>(table: &'a AstNode<'a>) -> RoffNode {
+    let alignments = match &table.data.borrow().value {
+        NodeValue::Table(alignments) => alignments.clone(),
+        _ => vec![],
+    };
+    let rows: Vec<(bool, Vec<String>)> = table
+        .children()
+        .map(|row| {
+            let header = matches!(row.data.borrow().value, NodeValue::TableRow(true));
+            let cells = row
+                .children()
+                .map(|cell| {
+                    let mut text = String::new();
+                    text_content(cell, &mut text);
+                    text.trim().to_string()
+                })
+                .collect();
+            (header, cells)
+        })
+        .collect();
+
+    let columns = column_count(&alignments, rows.iter().map(|(_, cells)| cells.len()));
+    let mut widths = vec![0; columns];
+    for (_, cells) in &rows {
+        for (width, cell) in widths.iter_mut().zip(cells) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut text = String::new();
+    for (header, cells) in &rows {
+        if *header && cells.iter().all(String::is_empty) {
+            continue;
+        }
+        let line = (0..columns)
+            .map(|i| {
+                let cell = cells.get(i).map(String::as_str).unwrap_or_default();
+                let padding = widths[i] - cell.chars().count();
+                match alignment_key(alignments.get(i)) {
+                    'r' => format!("{}{}", " ".repeat(padding), cell),
+                    'c' => format!(
+                        "{}{}{}",
+                        " ".repeat(padding / 2),
+                        cell,
+                        " ".repeat(padding - padding / 2)
+                    ),
+                    _ => format!("{}{}", cell, " ".repeat(padding)),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("  ");
+        text.push_str(line.trim_end());
+        text.push('\n');
+        if *header {
+            let width = widths.iter().sum::<usize>() + 2 * (columns - 1);
+            text.push_str(&"-".repeat(width));
+            text.push('\n');
+        }
+    }
+
+    RoffNode::paragraph([
+        raw::request(".nf"),
+        RoffNode::text(escape::code(&text)),
+        raw::request(".fi"),
+    ])
+}
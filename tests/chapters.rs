@@ -0,0 +1,344 @@
+mod common;
+
+use common::render;
+use mdbook::{
+    book::{Chapter, SectionNumber},
+    BookItem,
+};
+use mdbook_man::ChapterSelector;
+
+const CHAPTERS: &[(&str, &str)] = &[("One", "first"), ("Two", "second"), ("Three", "third")];
+
+#[test]
+fn chapters_are_not_separated_by_default() {
+    let rendered = render("", CHAPTERS);
+
+    assert!(!rendered.contains(".sp"));
+    assert!(!rendered.contains(".\\\""));
+}
+
+#[test]
+fn separator_is_only_inserted_between_chapters() {
+    let rendered = render("chapter-separator = \"comment\"", CHAPTERS);
+
+    assert_eq!(rendered.matches(".\\\" end of chapter").count(), 2);
+    assert!(rendered.contains("first\n.\\\" end of chapter\n.SH Two"));
+    assert!(rendered.trim_end().ends_with("third"));
+}
+
+#[test]
+fn rule_and_space_separators() {
+    let rule = render("chapter-separator = \"rule\"", CHAPTERS);
+    assert!(rule.contains("first\n.sp\n\\l'\\n(.lu'\n.SH Two"));
+
+    let space = render("chapter-separator = \"space\"", CHAPTERS);
+    assert!(space.contains("first\n.sp 2\n.SH Two"));
+}
+
+/// Returns the start of the header of every page up to its section number.
+fn page_titles(pages: &[mdbook_man::Roff]) -> Vec<String> {
+    pages
+        .iter()
+        .map(|page| {
+            let rendered = mdbook_man::roff_to_string(page).unwrap();
+            let header = rendered.lines().next().unwrap();
+            header.split(" \"\"").next().unwrap().to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn all_chapters_are_rendered_by_default() {
+    let ctx = common::book("test", "", CHAPTERS);
+    let pages = mdbook_man::mdbook_to_roff_chapters(&ctx).unwrap();
+
+    assert_eq!(
+        page_titles(&pages),
+        [".TH One 7", ".TH Two 7", ".TH Three 7"]
+    );
+}
+
+#[test]
+fn chapters_can_be_selected_in_config() {
+    let ctx = common::book("test", "chapters = [2, \"One\"]", CHAPTERS);
+    let pages = mdbook_man::mdbook_to_roff_chapters(&ctx).unwrap();
+
+    assert_eq!(page_titles(&pages), [".TH Three 7", ".TH One 7"]);
+}
+
+#[test]
+fn chapters_can_be_selected_with_api() {
+    let ctx = common::book("test", "", CHAPTERS);
+    let pages =
+        mdbook_man::mdbook_to_roff_selected_chapters(&ctx, &[ChapterSelector::Name("Two".into())])
+            .unwrap();
+
+    assert_eq!(page_titles(&pages), [".TH Two 7"]);
+}
+
+#[test]
+fn out_of_range_chapter_is_reported() {
+    let ctx = common::book("test", "", CHAPTERS);
+    let err = mdbook_man::mdbook_to_roff_selected_chapters(&ctx, &[ChapterSelector::Index(5)])
+        .unwrap_err();
+
+    assert_eq!(
+        err.to_string(),
+        "chapter `5` not found, the book has 3 chapters"
+    );
+}
+
+const UNSORTED: &[(&str, &str)] = &[("Usage", "u"), ("installation", "i"), ("About", "a")];
+
+/// Returns the titles of the chapter sections, leaving out the generated `NAME` section.
+fn section_titles(rendered: &str) -> Vec<&str> {
+    rendered
+        .lines()
+        .filter_map(|line| line.strip_prefix(".SH "))
+        .filter(|title| *title != "NAME")
+        .collect()
+}
+
+#[test]
+fn chapters_follow_summary_order_by_default() {
+    let rendered = render("", UNSORTED);
+
+    assert_eq!(
+        section_titles(&rendered),
+        ["Usage", "installation", "About"]
+    );
+}
+
+#[test]
+fn chapters_can_be_sorted_alphabetically() {
+    let rendered = render("chapter-order = \"alphabetical\"", UNSORTED);
+
+    assert_eq!(
+        section_titles(&rendered),
+        ["About", "installation", "Usage"]
+    );
+}
+
+#[test]
+fn chapters_can_be_ordered_explicitly() {
+    let rendered = render("chapter-order = [\"About\"]", UNSORTED);
+
+    assert_eq!(
+        section_titles(&rendered),
+        ["About", "Usage", "installation"]
+    );
+}
+
+#[test]
+fn unknown_chapter_order_is_an_error() {
+    let ctx = common::book("test", "chapter-order = \"random\"", UNSORTED);
+
+    assert!(mdbook_man::mdbook_to_roff(&ctx).is_err());
+}
+
+#[test]
+fn sections_are_not_prefixed_by_default() {
+    let ctx = common::book("mytool", "", &[("Usage", "u")]);
+    let rendered = mdbook_man::mdbook_to_string(&ctx).unwrap();
+
+    assert_eq!(section_titles(&rendered), ["Usage"]);
+}
+
+#[test]
+fn sections_can_be_prefixed_with_book_title() {
+    let ctx = common::book(
+        "mytool",
+        "prefix-sections = true",
+        &[("Usage", "u"), ("mytool configuration", "c")],
+    );
+    let rendered = mdbook_man::mdbook_to_string(&ctx).unwrap();
+
+    assert_eq!(
+        section_titles(&rendered),
+        ["\"mytool Usage\"", "\"mytool configuration\""]
+    );
+}
+
+#[test]
+fn sections_can_be_prefixed_with_custom_prefix() {
+    let rendered = render(
+        "prefix-sections = true\nsection-prefix = \"tool\"",
+        &[("Usage", "u")],
+    );
+
+    assert_eq!(section_titles(&rendered), ["\"tool Usage\""]);
+}
+
+#[test]
+fn separators_and_part_titles_take_no_index() {
+    let mut ctx = common::book("test", "chapters = [1]", &[]);
+    ctx.book.push_item(BookItem::PartTitle("Guide".into()));
+    ctx.book
+        .push_item(Chapter::new("One", "first".into(), "one.md", vec![]));
+    ctx.book.push_item(BookItem::Separator);
+    ctx.book
+        .push_item(Chapter::new("Two", "second".into(), "two.md", vec![]));
+
+    let pages = mdbook_man::mdbook_to_roff_chapters(&ctx).unwrap();
+
+    assert_eq!(page_titles(&pages), [".TH Two 7"]);
+}
+
+#[test]
+fn pages_are_named_after_their_chapters() {
+    let ctx = common::book("test", "chapter-order = \"alphabetical\"", CHAPTERS);
+    let pages = mdbook_man::mdbook_to_named_roff_chapters(&ctx).unwrap();
+
+    let names: Vec<_> = pages.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, ["One", "Three", "Two"]);
+    let (_, page) = &pages[1];
+    assert!(mdbook_man::roff_to_string(page)
+        .unwrap()
+        .starts_with(".TH Three 7"));
+}
+
+#[test]
+fn split_pages_refer_to_the_index_page() {
+    let ctx = common::book(
+        "mytool",
+        "split-chapters = true\nindex-page = \"mytool\"\nindex-section = 1\nsection = 5",
+        &[("Getting Started", "intro"), ("mytool-config", "config")],
+    );
+    let pages: Vec<_> = mdbook_man::mdbook_to_roff_chapters(&ctx)
+        .unwrap()
+        .iter()
+        .map(|page| mdbook_man::roff_to_string(page).unwrap())
+        .collect();
+
+    assert_eq!(pages.len(), 3);
+    for page in &pages[..2] {
+        assert!(
+            page.ends_with(".SH \"SEE ALSO\"\n.P\n\\fBmytool\\fR(1)"),
+            "{}",
+            page
+        );
+    }
+    let index = &pages[2];
+    assert!(index
+        .starts_with(".TH mytool 1 \"\" \"\" \"User Commands\"\n.SH NAME\n.P\nmytool \\- intro\n"));
+    assert!(
+        index.ends_with(
+            ".SH \"SEE ALSO\"\n.P\n\\fBGetting Started\\fR(5), \\fBmytool\\-config\\fR(5)"
+        ),
+        "{}",
+        index
+    );
+    assert_eq!(
+        mdbook_man::split_filenames(&ctx).unwrap(),
+        ["chapter0.man", "chapter1.man", "mytool.man"]
+    );
+}
+
+fn book_with_parts(config: &str) -> String {
+    let mut ctx = common::book("test", config, &[("Usage", "first")]);
+    ctx.book.push_item(BookItem::PartTitle("Guide".into()));
+    ctx.book
+        .push_item(Chapter::new("Usage", "second".into(), "guide.md", vec![]));
+    ctx.book
+        .push_item(Chapter::new("Other", "other".into(), "other.md", vec![]));
+    ctx.book
+        .push_item(Chapter::new("Usage", "third".into(), "third.md", vec![]));
+    mdbook_man::mdbook_to_string(&ctx).unwrap()
+}
+
+#[test]
+fn duplicate_section_titles_are_numbered() {
+    let rendered = book_with_parts("");
+
+    assert_eq!(
+        section_titles(&rendered),
+        ["Usage", "\"Usage (2)\"", "Other", "\"Usage (3)\""]
+    );
+}
+
+#[test]
+fn duplicate_section_titles_can_name_their_part() {
+    let rendered = book_with_parts("duplicate-sections = \"part\"");
+
+    assert_eq!(
+        section_titles(&rendered),
+        ["Usage", "\"Usage (Guide)\"", "Other", "\"Usage (3)\""]
+    );
+}
+
+#[test]
+fn duplicate_sections_can_be_merged() {
+    let rendered = book_with_parts("duplicate-sections = \"merge\"");
+
+    assert_eq!(section_titles(&rendered), ["Usage", "Other"]);
+    assert!(rendered.contains(".SH Usage\n.P\nfirst\n.P\nsecond\n.P\nthird\n.SH Other"));
+}
+
+#[test]
+fn split_pages_can_be_post_processed() {
+    let mut ctx = common::book("test", "index-page = \"test\"", &[("One", "first")]);
+    let mut chapter = Chapter::new("Two", "second".into(), "guide/two.md", vec![]);
+    chapter.number = Some(mdbook::book::SectionNumber(vec![1, 2]));
+    ctx.book.push_item(chapter);
+
+    let pages = mdbook_man::mdbook_to_roff_chapters_with(&ctx, |chapter, page| {
+        let number = format!("{:?}", chapter.number);
+        let path = chapter.path.as_ref().unwrap().display().to_string();
+        page.section("FOOTER", [format!("{} {} {}", chapter.name, number, path)])
+    })
+    .unwrap();
+    let pages: Vec<_> = pages
+        .iter()
+        .map(|page| mdbook_man::roff_to_string(page).unwrap())
+        .collect();
+
+    assert_eq!(pages.len(), 3);
+    assert!(
+        pages[0].ends_with(".SH FOOTER\nOne None one\\.md"),
+        "{}",
+        pages[0]
+    );
+    assert!(pages[1].ends_with(".SH FOOTER\nTwo Some([1, 2]) guide/two\\.md"));
+    assert!(!pages[2].contains("FOOTER"));
+}
+
+fn book_with_affixes(config: &str) -> String {
+    let mut ctx = common::book("test", config, &[("Foreword", "before")]);
+    for (i, name) in ["Usage", "Options"].iter().enumerate() {
+        let mut ch = Chapter::new(name, "numbered".into(), format!("{}.md", i), vec![]);
+        ch.number = Some(SectionNumber(vec![i as u32 + 1]));
+        ctx.book.push_item(ch);
+    }
+    ctx.book.push_item(Chapter::new(
+        "License",
+        "after".into(),
+        "license.md",
+        vec![],
+    ));
+    mdbook_man::mdbook_to_string(&ctx).unwrap()
+}
+
+#[test]
+fn prefix_and_suffix_chapters_stay_in_place_without_numbers() {
+    let rendered = book_with_affixes("");
+    assert_eq!(
+        section_titles(&rendered),
+        ["Foreword", "Usage", "Options", "License"]
+    );
+
+    let rendered = book_with_affixes("section-numbers = true");
+    assert_eq!(
+        section_titles(&rendered),
+        ["Foreword", "\"1\\. Usage\"", "\"2\\. Options\"", "License"]
+    );
+    assert!(rendered.contains(".SH Foreword\n.P\nbefore"));
+    assert!(rendered.ends_with(".SH License\n.P\nafter"), "{}", rendered);
+}
+
+#[test]
+fn prefix_and_suffix_chapters_can_be_skipped() {
+    let rendered = book_with_affixes("affix-chapters = \"skip\"");
+
+    assert_eq!(section_titles(&rendered), ["Usage", "Options"]);
+    assert!(!rendered.contains("before"));
+}
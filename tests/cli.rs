@@ -0,0 +1,353 @@
+mod common;
+
+use common::{book, run_renderer, run_renderer_with_env};
+use mdbook::{
+    book::{Chapter, SectionNumber},
+    BookItem,
+};
+
+#[test]
+fn page_on_stdout_ends_with_newline() {
+    let ctx = book("test", "", &[("Intro", "Hello\n")]);
+
+    let output = run_renderer(&ctx);
+
+    assert!(output.status.success());
+    let expected = format!("{}\n", mdbook_man::mdbook_to_string(&ctx).unwrap());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), expected);
+}
+
+#[test]
+fn page_on_stdout_without_trailing_newline() {
+    let ctx = book(
+        "test",
+        "no-trailing-newline = true",
+        &[("Intro", "Hello\n")],
+    );
+
+    let output = run_renderer(&ctx);
+
+    assert!(output.status.success());
+    let expected = mdbook_man::mdbook_to_string(&ctx).unwrap();
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), expected);
+}
+
+#[test]
+fn split_filenames_skip_separators_and_part_titles() {
+    let mut ctx = book("test", "split-chapters = true", &[]);
+    let dir = std::env::temp_dir().join(format!("mdbook-man-split-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    ctx.config
+        .set("output.man.output-dir", dir.to_str().unwrap())
+        .unwrap();
+    ctx.book.push_item(BookItem::PartTitle("Guide".into()));
+    ctx.book
+        .push_item(Chapter::new("One", "first".into(), "one.md", vec![]));
+    ctx.book.push_item(BookItem::Separator);
+    ctx.book
+        .push_item(Chapter::new("Two", "second".into(), "two.md", vec![]));
+
+    let output = run_renderer(&ctx);
+
+    assert!(output.status.success());
+    let mut files: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    files.sort();
+    assert_eq!(files, ["chapter0.man", "chapter1.man"]);
+    let second = std::fs::read_to_string(dir.join("chapter1.man")).unwrap();
+    assert!(second.starts_with(".TH Two 7"));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn invalid_config_is_reported() {
+    let ctx = book("test", "filename = \"tool.7\"", &[("Intro", "Hello\n")]);
+
+    let output = run_renderer(&ctx);
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("mdbook-man: invalid value of `output.man.filename`"));
+}
+
+#[test]
+fn combined_filename_defaults_to_title_slug() {
+    let dir = std::env::temp_dir().join(format!("mdbook-man-slug-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let config = format!("output-dir = {:?}", dir.to_str().unwrap());
+
+    let output = run_renderer(&book("My Tool", &config, &[("Intro", "Hello\n")]));
+    assert!(output.status.success());
+    assert!(dir.join("my-tool.man").exists());
+
+    let config = format!("{}\nfilename = \"tool.1\"", config);
+    let output = run_renderer(&book("My Tool", &config, &[("Intro", "Hello\n")]));
+    assert!(output.status.success());
+    assert!(dir.join("tool.1").exists());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn split_filenames_can_be_numbered() {
+    let mut ctx = book(
+        "test",
+        "split-chapters = true\nnumber-filenames = true",
+        &[],
+    );
+    for i in 1..=10 {
+        let mut chapter = Chapter::new(&format!("Part {}", i), "text".into(), "part.md", vec![]);
+        chapter.number = Some(SectionNumber(vec![i]));
+        ctx.book.push_item(chapter);
+    }
+    ctx.book.push_item(Chapter::new(
+        "Appendix",
+        "text".into(),
+        "appendix.md",
+        vec![],
+    ));
+
+    let filenames = mdbook_man::split_filenames(&ctx).unwrap();
+
+    assert_eq!(filenames[0], "01-part-1.man");
+    assert_eq!(filenames[9], "10-part-10.man");
+    assert_eq!(filenames[10], "appendix.man");
+
+    ctx.config
+        .set("output.man.number-filenames", false)
+        .unwrap();
+    assert_eq!(
+        mdbook_man::split_filenames(&ctx).unwrap()[0],
+        "chapter0.man"
+    );
+}
+
+#[test]
+fn empty_book_is_skipped_with_a_warning() {
+    let output = run_renderer(&book("test", "", &[]));
+
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("mdbook-man: warning: the book has no chapters with content"));
+
+    let output = run_renderer(&book("test", "", &[("Draft", "  \n")]));
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn empty_book_can_be_written_as_minimal_page() {
+    let ctx = book("test", "empty-book = \"page\"\nsplit-chapters = true", &[]);
+
+    let output = run_renderer(&ctx);
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with(".TH test 7"), "{}", stdout);
+    assert!(stdout.contains(".SH NAME\n"));
+    assert!(!output.stderr.is_empty());
+}
+
+#[test]
+fn environment_variables_override_the_config() {
+    let ctx = book(
+        "test",
+        "page-name = \"tool\"\nsection = 5",
+        &[("Intro", "Hello\n")],
+    );
+
+    let output = run_renderer_with_env(
+        &ctx,
+        &[
+            ("MDBOOK_MAN_PAGE_NAME", "other"),
+            ("MDBOOK_MAN_SECTION", "1"),
+            ("MDBOOK_MAN_DATE", "2024"),
+        ],
+    );
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.starts_with(".TH other 1 \"2024\" \"\" \"User Commands\"\n"),
+        "{}",
+        stdout
+    );
+
+    let output = run_renderer_with_env(&ctx, &[("MDBOOK_MAN_SECTION", "none")]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("`output.man.section`"), "{}", stderr);
+    assert!(stderr.contains("`MDBOOK_MAN_SECTION`"), "{}", stderr);
+}
+
+#[test]
+fn incremental_build_writes_only_changed_chapters() {
+    let dir = std::env::temp_dir().join(format!("mdbook-man-incremental-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let config = format!(
+        "split-chapters = true\nincremental = true\noutput-dir = {:?}",
+        dir.to_str().unwrap()
+    );
+
+    let ctx = book("test", &config, &[("One", "first"), ("Two", "second")]);
+    assert!(run_renderer(&ctx).status.success());
+    assert!(dir.join(".mdbook-man-manifest").exists());
+    // a page that is written again loses the marker
+    std::fs::write(dir.join("chapter1.man"), "unchanged").unwrap();
+
+    let ctx = book("test", &config, &[("One", "edited"), ("Two", "second")]);
+    assert!(run_renderer(&ctx).status.success());
+    let first = std::fs::read_to_string(dir.join("chapter0.man")).unwrap();
+    assert!(first.contains("edited"));
+    let second = std::fs::read_to_string(dir.join("chapter1.man")).unwrap();
+    assert_eq!(second, "unchanged");
+
+    let ctx = book("test", &config, &[("One", "edited")]);
+    assert!(run_renderer(&ctx).status.success());
+    assert!(dir.join("chapter0.man").exists());
+    assert!(!dir.join("chapter1.man").exists());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn incremental_build_rewrites_index_after_first_chapter_changes() {
+    let dir = std::env::temp_dir().join(format!("mdbook-man-index-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let config = format!(
+        "split-chapters = true\nincremental = true\nindex-page = \"tool\"\noutput-dir = {:?}",
+        dir.to_str().unwrap()
+    );
+
+    let ctx = book(
+        "test",
+        &config,
+        &[("One", "First version."), ("Two", "second")],
+    );
+    assert!(run_renderer(&ctx).status.success());
+    let index = std::fs::read_to_string(dir.join("tool.man")).unwrap();
+    assert!(index.contains("tool \\- First version"), "{}", index);
+
+    let ctx = book(
+        "test",
+        &config,
+        &[("One", "Second version."), ("Two", "second")],
+    );
+    assert!(run_renderer(&ctx).status.success());
+    let index = std::fs::read_to_string(dir.join("tool.man")).unwrap();
+    assert!(index.contains("tool \\- Second version"), "{}", index);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn split_pages_on_stdout_are_separated() {
+    let ctx = book(
+        "test",
+        "split-chapters = true",
+        &[("One", "first"), ("Two", "second")],
+    );
+
+    let output = run_renderer(&ctx);
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let pages: Vec<_> = stdout
+        .split(".\\\" mdbook-man page: chapter1.man\n")
+        .collect();
+    assert_eq!(pages.len(), 2, "{}", stdout);
+    assert!(pages[0].starts_with(".TH One 7"));
+    assert!(pages[0].ends_with("first\n"));
+    assert!(pages[1].starts_with(".TH Two 7"));
+
+    let ctx = book(
+        "test",
+        "split-chapters = true\nsplit-stdout = \"error\"",
+        &[("One", "first"), ("Two", "second")],
+    );
+    let output = run_renderer(&ctx);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("set `output-dir`"));
+}
+
+#[test]
+fn profile_can_be_selected_by_environment() {
+    let config = "[output.man.profiles.debian]\nsection = 1\nmanual = \"Debian Manual\"";
+    let ctx = book("test", config, &[("Intro", "Hello\n")]);
+
+    let output = run_renderer_with_env(&ctx, &[("MDBOOK_MAN_PROFILE", "debian")]);
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.starts_with(".TH test 1 \"\" \"\" \"Debian Manual\"\n"),
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn colliding_filenames_are_numbered_in_chapter_order() {
+    let dir = std::env::temp_dir().join(format!("mdbook-man-collisions-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let config = format!(
+        "split-chapters = true\nnumber-filenames = true\noutput-dir = {:?}",
+        dir.to_str().unwrap()
+    );
+    let ctx = book(
+        "test",
+        &config,
+        &[("Usage", "first"), ("usage", "second"), ("Usage!", "third")],
+    );
+
+    assert_eq!(
+        mdbook_man::split_filenames(&ctx).unwrap(),
+        ["usage.man", "usage-2.man", "usage-3.man"]
+    );
+    let output = run_renderer(&ctx);
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("warning: more than one page is saved to `usage.man`"));
+    for (filename, content) in [
+        ("usage.man", "first"),
+        ("usage-2.man", "second"),
+        ("usage-3.man", "third"),
+    ] {
+        let page = std::fs::read_to_string(dir.join(filename)).unwrap();
+        assert!(page.contains(content), "{}", page);
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn aliases_are_written_as_so_stubs() {
+    let dir = std::env::temp_dir().join(format!("mdbook-man-aliases-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let config = format!(
+        "split-chapters = true\nnumber-filenames = true\noutput-dir = {:?}\n\n[output.man.aliases]\n\"foo-config.5\" = \"man5/foo.conf.5\"",
+        dir.to_str().unwrap()
+    );
+    let ctx = book("test", &config, &[("foo.conf", "options")]);
+
+    let output = run_renderer(&ctx);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(dir.join("foo-conf.man").exists());
+    assert_eq!(
+        std::fs::read_to_string(dir.join("foo-config.5")).unwrap(),
+        ".so man5/foo.conf.5\n"
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let config = format!(
+        "split-chapters = true\nnumber-filenames = true\noutput-dir = {:?}\n\n[output.man.aliases]\n\"foo-conf.man\" = \"man5/foo.conf.5\"",
+        dir.to_str().unwrap()
+    );
+    let ctx = book("test", &config, &[("foo.conf", "options")]);
+    let output = run_renderer(&ctx);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("output.man.aliases"), "{}", stderr);
+    // nothing is written before the aliases are checked
+    assert!(!dir.exists());
+}
@@ -0,0 +1,235 @@
+mod common;
+
+use common::render;
+
+#[test]
+fn indented_code_block_renders_like_fenced() {
+    let indented = render(
+        "",
+        &[("Code", "Some code:\n\n    let x = 1;\n    let y = 2;\n")],
+    );
+    let fenced = render(
+        "",
+        &[("Code", "Some code:\n\n```\nlet x = 1;\nlet y = 2;\n```\n")],
+    );
+
+    assert_eq!(indented, fenced);
+    assert!(indented.contains(".IP \"\" 2"));
+    assert!(!indented.contains("\\fB\\fR"));
+}
+
+fn code_block_with_info(info: &str) -> String {
+    let content = format!("```{}\nfn main() {{}}\n```\n", info);
+    render("", &[("Code", &content)])
+}
+
+#[test]
+fn plain_language_is_used_as_title() {
+    assert!(code_block_with_info("rust").contains(".IP \\fBrust\\fR 2"));
+}
+
+#[test]
+fn attributes_are_stripped_from_title() {
+    let expected = ".IP \\fBrust\\fR 2";
+
+    assert!(code_block_with_info("rust,no_run").contains(expected));
+    assert!(code_block_with_info("{.rust}").contains(expected));
+    assert!(code_block_with_info("{.rust .numberLines}").contains(expected));
+}
+
+#[test]
+fn attributes_only_produce_no_title() {
+    let rendered = code_block_with_info("ignore");
+
+    assert!(rendered.contains(".IP \"\" 2"));
+    assert!(!rendered.contains("ignore"));
+}
+
+const WITH_CODE: &str = "Before\n\n```rust\nfn main() {}\n```\n\nAfter\n";
+
+#[test]
+fn code_blocks_can_be_omitted() {
+    let rendered = render("omit-code-blocks = true", &[("Code", WITH_CODE)]);
+
+    assert!(!rendered.contains(".EX"));
+    assert!(!rendered.contains("fn main"));
+    assert!(rendered.contains("Before"));
+    assert!(rendered.contains("After"));
+}
+
+#[test]
+fn omitted_code_blocks_can_be_replaced_with_placeholder() {
+    let rendered = render(
+        "omit-code-blocks = true\ncode-block-placeholder = \"[example omitted]\"",
+        &[("Code", WITH_CODE)],
+    );
+
+    assert!(!rendered.contains(".EX"));
+    assert!(rendered.contains("\\fI[example omitted]\\fR"));
+}
+
+const SNIPPET: &str = "```rust\nfn main() {\n    println!(\"hi\");\n}\n```\n";
+
+#[test]
+fn example_style_is_default() {
+    let rendered = render("", &[("Code", SNIPPET)]);
+
+    assert!(rendered.contains(".EX\nfn main() {\n    println!(\"hi\");\n}\n"));
+    assert!(!rendered.contains(".nf"));
+}
+
+#[test]
+fn verbatim_style_uses_no_fill_paragraph() {
+    let rendered = render("code-block-style = \"verbatim\"", &[("Code", SNIPPET)]);
+
+    assert!(rendered.contains(".nf\nfn main() {\n    println!(\"hi\");\n}\n.fi\n"));
+    assert!(!rendered.contains(".EX"));
+}
+
+#[test]
+fn backslashes_in_code_blocks_are_escaped() {
+    let rendered = render(
+        "",
+        &[("Code", "```\ncd C:\\Users\n\\d+\\s*\n'quoted\n```\n")],
+    );
+
+    assert!(rendered.contains("cd C:\\eUsers\n\\ed+\\es*\n\\&'quoted\n"));
+}
+
+#[test]
+fn backslashes_in_verbatim_code_blocks_are_escaped() {
+    let rendered = render(
+        "code-block-style = \"verbatim\"",
+        &[("Code", "```\n\\begin{document}\n```\n")],
+    );
+
+    assert!(rendered.contains(".nf\n\\ebegin{document}\n.fi\n"));
+}
+
+#[test]
+fn blank_lines_in_code_blocks_are_preserved() {
+    let rendered = render("", &[("Code", "```\nlet a = 1;\n\nlet b = 2;\n```\n")]);
+
+    assert!(rendered.contains(".EX\nlet a = 1;\n\\&\nlet b = 2;\n\n.EE"));
+}
+
+#[test]
+fn long_code_lines_are_kept_by_default() {
+    let line = "x".repeat(120);
+    let rendered = render("", &[("Code", &format!("```\n{}\n```\n", line))]);
+
+    assert!(rendered.contains(&format!("\n{}\n", line)));
+}
+
+#[test]
+fn long_code_lines_can_be_wrapped() {
+    let line = "x".repeat(120);
+    let rendered = render(
+        "code-wrap-width = 78",
+        &[("Code", &format!("```\n{}\nshort\n```\n", line))],
+    );
+
+    let expected = format!("\n{}\\(rA\n{}\nshort\n", "x".repeat(77), "x".repeat(43));
+    assert!(rendered.contains(&expected));
+}
+
+#[test]
+fn wrapped_lines_differ_from_trailing_backslashes() {
+    let rendered = render(
+        "code-wrap-width = 10",
+        &[("Code", "```\nline \\\nlong line of code\n```\n")],
+    );
+
+    assert!(
+        rendered.contains("\nline \\e\nlong line\\(rA\n of code\n"),
+        "{}",
+        rendered
+    );
+}
+
+const CODE_ONLY: &[(&str, &str)] = &[("Examples", "```console\n$ tool --help\n```\n")];
+
+#[test]
+fn code_only_chapter_is_a_well_formed_page() {
+    let ctx = common::book("tool", "split-chapters = true", CODE_ONLY);
+    let pages = mdbook_man::mdbook_to_roff_chapters(&ctx).unwrap();
+    let rendered = mdbook_man::roff_to_string(&pages[0]).unwrap();
+
+    assert!(rendered.starts_with(".TH Examples 7 \"\" \"\" \"Miscellaneous\"\n.SH Examples\n.RS\n"));
+    assert!(rendered.contains("$ tool \\-\\-help"));
+    assert!(mdbook_man::lint_page(&rendered).is_empty());
+}
+
+#[test]
+fn code_only_chapter_can_be_named() {
+    let config = "split-chapters = true\nname-code-chapters = true";
+    let ctx = common::book("tool", config, CODE_ONLY);
+    let pages = mdbook_man::mdbook_to_roff_chapters(&ctx).unwrap();
+    let rendered = mdbook_man::roff_to_string(&pages[0]).unwrap();
+
+    assert!(rendered.starts_with(".TH Examples 7 \"\" \"\" \"Miscellaneous\"\n.SH NAME\n.P\nExamples \\- tool\n.SH Examples\n"));
+
+    let ctx = common::book("tool", config, &[("Prose", "Some text\n")]);
+    let pages = mdbook_man::mdbook_to_roff_chapters(&ctx).unwrap();
+    assert!(!mdbook_man::roff_to_string(&pages[0])
+        .unwrap()
+        .contains("NAME"));
+}
+
+#[test]
+fn title_style_is_configurable() {
+    let code = &[("Code", "```rust\nfn main() {}\n```\n")];
+
+    assert!(render("code-title-style = \"italic\"", code).contains(".IP \\fIrust\\fR 2\n"));
+    assert!(render(
+        "code-title-style = \"roman\"\ncode-title-prefix = \"Example: \"",
+        code
+    )
+    .contains(".IP \"Example: rust\" 2\n"));
+    assert!(render("code-title-prefix = \"Example: \"", code)
+        .contains(".IP \"\\fBExample: rust\\fR\" 2\n"));
+
+    let rendered = render("code-title-style = \"none\"", code);
+    assert!(rendered.contains(".IP \"\" 2\n"));
+    assert!(!rendered.contains("rust"));
+}
+
+const EXAMPLES: &[(&str, &str)] = &[
+    (
+        "Usage",
+        "Run the tool.\n\nTo list all files:\n\n```sh,example\nls -a\n```\n\nInline:\n\n```sh\nls\n```\n",
+    ),
+    ("Files", "Read from:\n\n```demo\n/etc/tool\n```\n"),
+];
+
+#[test]
+fn tagged_code_blocks_move_to_examples_section() {
+    let rendered = render("examples = true", EXAMPLES);
+
+    let (body, examples) = rendered.split_once(".SH EXAMPLES\n").unwrap();
+    assert!(
+        body.contains(".P\nInline:\n.RS\n.IP \\fBsh\\fR 2\n\n.br\n.EX\nls\n\n.EE\n"),
+        "{}",
+        rendered
+    );
+    assert!(!body.contains("ls \\-a"));
+    assert!(!body.contains("To list all files"));
+    assert!(
+        examples.starts_with(
+            ".P\nTo list all files:\n.RS\n.IP \\fBsh\\fR 2\n\n.br\n.EX\nls \\-a\n\n.EE\n"
+        ),
+        "{}",
+        rendered
+    );
+    assert!(!examples.contains("/etc/tool"));
+
+    let rendered = render("examples = true\nexample-tag = \"demo\"", EXAMPLES);
+    let examples = rendered.split_once(".SH EXAMPLES\n").unwrap().1;
+    assert!(
+        examples.starts_with(".P\nRead from:\n.RS\n.IP \"\" 2\n\n.br\n.EX\n/etc/tool\n"),
+        "{}",
+        rendered
+    );
+
+    assert!(!render("", EXAMPLES).contains("EXAMPLES"));
+}
@@ -0,0 +1,67 @@
+#![allow(dead_code)]
+
+use mdbook::{
+    book::{Book, Chapter},
+    renderer::RenderContext,
+    Config, MDBook,
+};
+
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Output, Stdio},
+    str::FromStr,
+};
+
+pub fn fixture_path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(name)
+}
+
+/// Loads a fixture book from `tests/fixtures/<name>` into a `RenderContext`.
+pub fn fixture_book(name: &str) -> RenderContext {
+    let root = fixture_path(name);
+    let book = MDBook::load(&root).unwrap();
+    RenderContext::new(&root, book.book, book.config, root.join("book"))
+}
+
+/// Builds a `RenderContext` of a book titled `title` with `config` appended to its `book.toml`
+/// and a chapter for each `(name, content)` pair.
+pub fn book(title: &str, config: &str, chapters: &[(&str, &str)]) -> RenderContext {
+    let config = format!("[book]\ntitle = {:?}\n\n[output.man]\n{}", title, config);
+    let config = Config::from_str(&config).unwrap();
+
+    let mut book = Book::new();
+    for (name, content) in chapters {
+        let path = format!("{}.md", name.to_lowercase().replace(' ', "-"));
+        book.push_item(Chapter::new(name, content.to_string(), path, vec![]));
+    }
+
+    RenderContext::new("/tmp/book", book, config, "/tmp/book/book")
+}
+
+/// Renders the book built with [`book`](book) as a single man page.
+pub fn render(config: &str, chapters: &[(&str, &str)]) -> String {
+    mdbook_man::mdbook_to_string(&book("test", config, chapters)).unwrap()
+}
+
+/// Runs the `mdbook-man` binary with `ctx` passed on its stdin like mdBook does.
+pub fn run_renderer(ctx: &RenderContext) -> Output {
+    run_renderer_with_env(ctx, &[])
+}
+
+/// Same as [`run_renderer`](run_renderer) with the variables of `env` set for the binary.
+pub fn run_renderer_with_env(ctx: &RenderContext, env: &[(&str, &str)]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_mdbook-man"))
+        .envs(env.iter().copied())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let input = serde_json::to_vec(ctx).unwrap();
+    child.stdin.take().unwrap().write_all(&input).unwrap();
+    child.wait_with_output().unwrap()
+}
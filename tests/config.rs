@@ -0,0 +1,136 @@
+mod common;
+
+use mdbook_man::{Error, ManOutputConfiguration};
+
+#[test]
+fn invalid_config_reports_key() {
+    let ctx = common::book("test", "split-chapters = \"yes\"", &[]);
+
+    match ManOutputConfiguration::load(&ctx) {
+        Err(Error::Config { key, message }) => {
+            assert_eq!(key, "output.man");
+            assert!(message.contains("split-chapters"), "{}", message);
+        }
+        other => panic!("expected a config error, got {:?}", other),
+    }
+}
+
+#[test]
+fn invalid_config_names_the_field() {
+    for config in ["section = \"abc\"", "chapter-separator = \"dots\""] {
+        let ctx = common::book("test", config, &[]);
+        let option = config.split(' ').next().unwrap();
+
+        match ManOutputConfiguration::load(&ctx) {
+            Err(error) => assert!(error.to_string().contains(option), "{}", error),
+            other => panic!("expected a config error for `{}`, got {:?}", config, other),
+        }
+    }
+}
+
+#[test]
+fn missing_config_uses_defaults() {
+    let ctx = common::book("test", "", &[]);
+    let cfg = ManOutputConfiguration::load(&ctx).unwrap();
+
+    assert!(!cfg.split_chapters);
+    assert!(cfg.output_dir.is_none());
+}
+
+fn invalid_key(config: &str) -> String {
+    let ctx = common::book("test", config, &[]);
+
+    match ManOutputConfiguration::load(&ctx) {
+        Err(Error::Config { key, .. }) => key,
+        other => panic!("expected a config error for `{}`, got {:?}", config, other),
+    }
+}
+
+#[test]
+fn valid_config_passes_validation() {
+    let ctx = common::book(
+        "test",
+        "output-dir = \"man\"\nfilename = \"tool.7\"\ncode-wrap-width = 80",
+        &[],
+    );
+    let cfg = ManOutputConfiguration::load(&ctx).unwrap();
+
+    assert!(cfg.validate().is_ok());
+}
+
+#[test]
+fn filename_requires_output_dir() {
+    assert_eq!(invalid_key("filename = \"tool.7\""), "output.man.filename");
+}
+
+#[test]
+fn filename_conflicts_with_split_chapters() {
+    assert_eq!(
+        invalid_key("output-dir = \"man\"\nsplit-chapters = true\nfilename = \"tool.7\""),
+        "output.man.filename"
+    );
+}
+
+#[test]
+fn filename_must_not_contain_directories() {
+    assert_eq!(
+        invalid_key("output-dir = \"man\"\nfilename = \"man7/tool.7\""),
+        "output.man.filename"
+    );
+}
+
+#[test]
+fn split_stdout_error_requires_output_dir() {
+    assert_eq!(
+        invalid_key("split-chapters = true\nsplit-stdout = \"error\""),
+        "output.man.split-stdout"
+    );
+
+    let ctx = common::book(
+        "test",
+        "split-chapters = true\nsplit-stdout = \"error\"\noutput-dir = \"man\"",
+        &[],
+    );
+    assert!(ManOutputConfiguration::load(&ctx).is_ok());
+}
+
+#[test]
+fn numeric_options_must_be_in_range() {
+    assert_eq!(
+        invalid_key("code-wrap-width = 1"),
+        "output.man.code-wrap-width"
+    );
+    assert_eq!(invalid_key("line-length = 0"), "output.man.line-length");
+    assert_eq!(invalid_key("max-depth = 0"), "output.man.max-depth");
+    assert_eq!(invalid_key("section = 0"), "output.man.section");
+    assert_eq!(
+        invalid_key("index-section = 10"),
+        "output.man.index-section"
+    );
+    assert_eq!(
+        invalid_key("break-long-words = 0"),
+        "output.man.break-long-words"
+    );
+    assert_eq!(invalid_key("list-indent = 0"), "output.man.list-indent");
+    assert_eq!(invalid_key("truncate-urls = 0"), "output.man.truncate-urls");
+    assert_eq!(
+        invalid_key("example-tag = \"my example\""),
+        "output.man.example-tag"
+    );
+    assert_eq!(
+        invalid_key("[output.man.aliases]\n\"a.5\" = \"man5/b.5\""),
+        "output.man.aliases"
+    );
+    assert_eq!(
+        invalid_key("output-dir = \"man\"\n[output.man.aliases]\n\"man5/a.5\" = \"man5/b.5\""),
+        "output.man.aliases"
+    );
+}
+
+#[test]
+fn chapter_order_must_not_repeat_chapters() {
+    assert_eq!(
+        invalid_key("chapter-order = [\"One\", \"Two\", \"One\"]"),
+        "output.man.chapter-order"
+    );
+}
@@ -0,0 +1,221 @@
+mod common;
+
+use common::render;
+
+fn body(config: &str, markdown: &str) -> String {
+    let rendered = render(config, &[("Body", markdown)]);
+    rendered.split_once(".SH Body\n").unwrap().1.to_string()
+}
+
+#[test]
+fn default_extensions_are_gfm_like() {
+    let rendered = body(
+        "",
+        "| a |\n|---|\n| b |\n\n~~old~~ x^2^ https://example.com[^1]\n\n[^1]: note\n",
+    );
+
+    assert!(rendered.contains(".TS\n"));
+    assert!(rendered.contains("[\\-old\\-]"));
+    assert!(rendered.contains("x^2^ "));
+    assert!(!rendered.contains(".UR"));
+    assert!(rendered.contains("[1]"));
+}
+
+#[test]
+fn extensions_can_be_disabled() {
+    let config = "tables = false\nstrikethrough = false\ntasklist = false\nfootnotes = false";
+    let rendered = body(
+        &format!("{}\ntask-checked = \"done\"", config),
+        "| a |\n|---|\n| b |\n\n~~old~~ text[^1]\n\n- [x] task\n\n[^1]: note\n",
+    );
+
+    assert!(!rendered.contains(".TS"));
+    assert!(rendered.contains("| a |"), "{}", rendered);
+    assert!(rendered.contains("~~old~~"));
+    assert!(!rendered.contains("NOTES"));
+    assert!(rendered.contains("[x] task"));
+    assert!(!rendered.contains("done"));
+}
+
+#[test]
+fn extensions_can_be_enabled() {
+    let rendered = body(
+        "autolink = true\ndescription-lists = true\nsuperscript = true",
+        "Term\n\n: the *details*\n\n  more details\n\nx^2^ at https://example.com\n",
+    );
+
+    assert!(
+        rendered.contains(".TP\n\\fBTerm\\fR\nthe \\fIdetails\\fR\n.IP\nmore details\n"),
+        "{}",
+        rendered
+    );
+    assert!(rendered.contains("x^2^ at \n.UR https://example\\.com\n.UE"));
+}
+
+#[test]
+fn gfm_preset_enables_its_extensions() {
+    let markdown = "| a |\n|---|\n| b |\n\n~~old~~ at https://example.com\n";
+    let rendered = body("gfm = true", markdown);
+
+    assert!(rendered.contains(".TS\n"));
+    assert!(rendered.contains("[\\-old\\-]"));
+    assert!(rendered.contains(".UR https://example\\.com\n"));
+
+    let rendered = body("gfm = true\nautolink = false\ntables = false", markdown);
+    assert!(!rendered.contains(".TS"));
+    assert!(!rendered.contains(".UR"));
+    assert!(rendered.contains("[\\-old\\-]"));
+}
+
+#[test]
+fn gfm_preset_disables_its_extensions() {
+    let markdown = "| a |\n|---|\n| b |\n\n~~old~~ at https://example.com\n\n- [x] task\n";
+    let rendered = body("gfm = false\ntask-checked = \"done\"", markdown);
+
+    assert!(!rendered.contains(".TS"));
+    assert!(rendered.contains("~~old~~"));
+    assert!(!rendered.contains(".UR"));
+    assert!(rendered.contains("[x] task"));
+    assert!(!rendered.contains("done"));
+
+    let rendered = body("gfm = false\ntables = true", markdown);
+    assert!(rendered.contains(".TS\n"));
+    assert!(rendered.contains("~~old~~"));
+}
+
+#[test]
+fn abbreviations_are_expanded_on_first_use() {
+    let markdown = "*[HTML]: HyperText Markup Language\n\nWrite HTML or XHTML.\n\nMore HTML.\n";
+
+    let rendered = body("abbreviations = true", markdown);
+    assert_eq!(
+        rendered,
+        ".P\nWrite HTML (HyperText Markup Language) or XHTML\\.\n.P\nMore HTML\\."
+    );
+
+    let rendered = body("", markdown);
+    assert!(!rendered.contains("(HyperText"));
+}
+
+#[test]
+fn math_keeps_its_source() {
+    let markdown = "Energy is *$E = mc^2$* for $5 or $6.\n\n$$\n\\int_0^1 x\\,dx\n$$\n";
+
+    let rendered = body("math = true", markdown);
+    assert!(
+        rendered.contains(".P\nEnergy is E = mc^2 for $5 or $6\\.\n"),
+        "{}",
+        rendered
+    );
+    assert!(rendered.contains(".IP \"\" 2\n"));
+    assert!(rendered.contains(".EX\n\\eint_0^1 x\\e,dx\n"));
+    assert!(!rendered.contains("math"));
+
+    let rendered = body("math = true", "```math\na^2 + b^2\n```\n\n`$HOME` is $x$\n");
+    assert!(rendered.contains(".EX\na^2 + b^2\n"));
+    assert!(rendered.contains("`\\fI$HOME\\fR` is x"));
+
+    let rendered = body("", markdown);
+    assert!(rendered.contains("$E = mc^2$"));
+}
+
+#[test]
+fn math_is_left_alone_in_code_and_html() {
+    let markdown = "Run:\n\n    echo $a$b\n\n* item\n\n      echo $c$d\n\nSee <span title=\"$x$\">this</span> and $y$.\n";
+
+    let rendered = body("math = true\nunsafe-html = true", markdown);
+    assert!(rendered.contains(".EX\necho $a$b\n"), "{}", rendered);
+    assert!(rendered.contains(".EX\necho $c$d\n"), "{}", rendered);
+    assert!(
+        rendered.contains("<span title=\"$x$\">this</span> and y\\."),
+        "{}",
+        rendered
+    );
+    assert!(!rendered.contains('`'), "{}", rendered);
+}
+
+#[test]
+fn math_outside_the_body_text_has_no_marker() {
+    let markdown =
+        "# Euler $e^x$\n\nThe $a$ value[^1].\n\n| m |\n|---|\n| $b$ |\n\n[^1]: note $c$\n";
+    let config = "math = true\ncheck = true\nheading-as-title = true\ntable-style = \"plain\"\nfootnote-style = \"inline\"";
+
+    let rendered = render(config, &[("Body", markdown)]);
+    assert!(
+        rendered.contains(".SH \"Euler \\fRe^x\\fP\"\n"),
+        "{}",
+        rendered
+    );
+    assert!(
+        rendered.contains("The a value (note c)\\.\n"),
+        "{}",
+        rendered
+    );
+    assert!(rendered.contains(".nf\nm\n\\-\nb\n.fi\n"), "{}", rendered);
+    assert!(!rendered.contains('\u{FDD2}'));
+}
+
+#[test]
+fn quote_attributions_are_italic_on_their_own_line() {
+    let chapters = &[("Quote", "> Simple is better.\n> -- Tim Peters\n")];
+
+    let rendered = render("", chapters);
+    assert!(
+        rendered.contains("Simple is better\\. \\-\\- Tim Peters"),
+        "{}",
+        rendered
+    );
+
+    let rendered = render("quote-attributions = true", chapters);
+    assert!(
+        rendered.contains(".P\nSimple is better\\.\n.br\n\\fI— Tim Peters\\fR"),
+        "{}",
+        rendered
+    );
+
+    let rendered = render(
+        "quote-attributions = true",
+        &[(
+            "Quote",
+            "> Simple is better.\n>\n> — Tim Peters\n\n> — not an attribution\n",
+        )],
+    );
+    assert!(
+        rendered.contains(".P\n\\fI— Tim Peters\\fR"),
+        "{}",
+        rendered
+    );
+    assert!(
+        rendered.contains(".P\n— not an attribution"),
+        "{}",
+        rendered
+    );
+}
+
+#[test]
+fn description_details_can_hold_blocks() {
+    let rendered = body(
+        "description-lists = true",
+        "`--color`\n\n: Colors the output.\n\n  Defaults to `auto`.\n\n  ```\n  tool --color\n  ```\n\n  * `always`\n  * `never`\n\nAfter\n",
+    );
+
+    assert!(
+        rendered.contains(
+            ".TP\n\\fB\\-\\-color\\fR\nColors the output\\.\n.IP\nDefaults to `\\fIauto\\fR`\\.\n.RS\n"
+        ),
+        "{}",
+        rendered
+    );
+    assert!(
+        rendered.contains(".EX\ntool \\-\\-color\n\n.EE\n\n.RE\n"),
+        "{}",
+        rendered
+    );
+    assert!(
+        rendered.contains(
+            ".RS\n.IP \\(bu 4\n`\\fIalways\\fR`\n.IP \\(bu 4\n`\\fInever\\fR`\n.RE\n.P\nAfter"
+        ),
+        "{}",
+        rendered
+    );
+}
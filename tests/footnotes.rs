@@ -0,0 +1,47 @@
+mod common;
+
+use common::render;
+
+const FOOTNOTES: &str =
+    "Text[^b] and[^a] again[^b].\n\n[^a]: Note a.\n[^b]: Note b.\n[^c]: Unused.\n";
+
+#[test]
+fn references_are_numbered_by_first_reference() {
+    let rendered = render("", &[("Notes", FOOTNOTES)]);
+
+    assert!(rendered.contains("Text[1] and[2] again[1]\\."));
+}
+
+#[test]
+fn definitions_are_rendered_once_under_notes() {
+    let rendered = render("", &[("Notes", FOOTNOTES)]);
+
+    assert!(rendered.contains("\\fBNOTES\\fR"));
+    assert!(rendered.contains(".IP [1] 4\nNote b\\.\n.IP [2] 4\nNote a\\.\n"));
+    assert_eq!(rendered.matches("Note b").count(), 1);
+    assert!(!rendered.contains("Unused"));
+}
+
+#[test]
+fn split_pages_get_a_notes_section() {
+    let ctx = common::book("test", "", &[("Notes", FOOTNOTES)]);
+    let pages = mdbook_man::mdbook_to_roff_chapters(&ctx).unwrap();
+    let rendered = mdbook_man::roff_to_string(&pages[0]).unwrap();
+
+    assert!(rendered.contains(".SH NOTES\n.IP [1] 4\nNote b\\.\n"));
+}
+
+#[test]
+fn definitions_can_be_rendered_inline() {
+    let rendered = render("footnote-style = \"collect\"", &[("Notes", FOOTNOTES)]);
+    assert!(rendered.contains("Text[1] and[2] again[1]\\."));
+
+    let rendered = render("footnote-style = \"inline\"", &[("Notes", FOOTNOTES)]);
+    assert!(
+        rendered.contains("Text (Note b\\.) and (Note a\\.) again (Note b\\.)\\."),
+        "{}",
+        rendered
+    );
+    assert!(!rendered.contains("NOTES"));
+    assert!(!rendered.contains("[1]"));
+}
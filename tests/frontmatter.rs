@@ -0,0 +1,135 @@
+mod common;
+
+const CHAPTERS: &[(&str, &str)] = &[
+    (
+        "mytool",
+        "---\ntitle: ignored\nsection: 1\n---\n\nSee [the format](mytool-format.md).\n",
+    ),
+    (
+        "mytool-format",
+        "---\nsection: 5\npage-name: \"mytool.conf\"\n---\nThe format.\n",
+    ),
+    ("Notes", "---\nman-page: false\n---\nNot a page.\n"),
+];
+
+fn split_pages(config: &str) -> Vec<String> {
+    let ctx = common::book("mytool", config, CHAPTERS);
+    mdbook_man::mdbook_to_roff_chapters(&ctx)
+        .unwrap()
+        .iter()
+        .map(|page| mdbook_man::roff_to_string(page).unwrap())
+        .collect()
+}
+
+#[test]
+fn front_matter_overrides_the_section() {
+    let pages = split_pages("section = 7");
+
+    assert!(pages[0].starts_with(".TH mytool 1 \"\" \"\" \"User Commands\"\n"));
+    assert!(
+        pages[1].starts_with(".TH mytool\\.conf 5 \"\" \"\" \"File Formats\"\n"),
+        "{}",
+        pages[1]
+    );
+    assert!(
+        pages[0].contains("(see \\fBmytool\\.conf\\fR(5))"),
+        "{}",
+        pages[0]
+    );
+}
+
+#[test]
+fn chapters_can_be_left_out_of_split_pages() {
+    let ctx = common::book("mytool", "", CHAPTERS);
+
+    assert_eq!(split_pages("").len(), 2);
+    assert_eq!(mdbook_man::split_filenames(&ctx).unwrap().len(), 2);
+}
+
+#[test]
+fn front_matter_is_not_rendered() {
+    let rendered = common::render("", CHAPTERS);
+
+    assert!(!rendered.contains("section: 1"));
+    assert!(!rendered.contains("ignored"));
+    assert!(
+        rendered.contains(".SH NAME\n.P\ntest \\- See the format\n"),
+        "{}",
+        rendered
+    );
+    assert!(rendered.contains("Not a page"));
+}
+
+#[test]
+fn invalid_front_matter_is_reported() {
+    let ctx = common::book("mytool", "", &[("mytool", "---\nsection: 10\n---\ntext\n")]);
+
+    let err = mdbook_man::mdbook_to_roff_chapters(&ctx).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid value of `section` in the front matter of chapter `mytool` - must be between 1 and 9"
+    );
+    assert!(matches!(
+        err,
+        mdbook_man::Error::FrontMatter { chapter, key, .. } if chapter == "mytool" && key == "section"
+    ));
+}
+
+#[test]
+fn opening_thematic_break_is_not_front_matter() {
+    let rendered = common::render(
+        "",
+        &[("Intro", "---\n\nImportant paragraph.\n\n---\n\nTail.\n")],
+    );
+    assert!(rendered.contains("Important paragraph"), "{}", rendered);
+    assert!(rendered.contains("Tail"), "{}", rendered);
+
+    let rendered = common::render("", &[("Intro", "---\nImportant paragraph.\n---\nTail.\n")]);
+    assert!(rendered.contains("Important paragraph"), "{}", rendered);
+}
+
+#[test]
+fn file_page_and_section_names_can_all_differ() {
+    let ctx = common::book(
+        "mytool",
+        "number-filenames = true",
+        &[(
+            "mytool-config",
+            "---\nfilename: mytool-config.5\npage-name: mytool.conf\nsection-title: Configuration\n---\ntext\n",
+        )],
+    );
+
+    let pages = mdbook_man::mdbook_to_roff_chapters(&ctx).unwrap();
+    let rendered = mdbook_man::roff_to_string(&pages[0]).unwrap();
+    assert!(rendered
+        .starts_with(".TH mytool\\.conf 7 \"\" \"\" \"Miscellaneous\"\n.SH Configuration\n"));
+    assert_eq!(
+        mdbook_man::split_filenames(&ctx).unwrap(),
+        ["mytool-config.5"]
+    );
+
+    let ctx = common::book(
+        "mytool",
+        "number-filenames = true",
+        &[("mytool-config", "text\n")],
+    );
+    let rendered =
+        mdbook_man::roff_to_string(&mdbook_man::mdbook_to_roff_chapters(&ctx).unwrap()[0]).unwrap();
+    assert!(rendered
+        .starts_with(".TH mytool\\-config 7 \"\" \"\" \"Miscellaneous\"\n.SH mytool\\-config\n"));
+    assert_eq!(
+        mdbook_man::split_filenames(&ctx).unwrap(),
+        ["mytool-config.man"]
+    );
+}
+
+#[test]
+fn unhandled_lines_count_the_front_matter() {
+    let chapter = "---\nsection: 1\ntitle: ignored\n---\n\nSome text.\n\nSee <span>this</span>.\n";
+    let ctx = common::book("test", "strict = true", &[("Html", chapter)]);
+
+    match mdbook_man::mdbook_to_string(&ctx) {
+        Err(mdbook_man::Error::Unhandled { line, .. }) => assert_eq!(line, 8),
+        other => panic!("expected an unhandled element, got {:?}", other),
+    }
+}
@@ -0,0 +1,275 @@
+mod common;
+
+const CHAPTERS: &[(&str, &str)] = &[("mytool", "main page"), ("mytool-config", "config")];
+
+fn split_headers(config: &str) -> Vec<String> {
+    let ctx = common::book("mytool", config, CHAPTERS);
+    mdbook_man::mdbook_to_roff_chapters(&ctx)
+        .unwrap()
+        .iter()
+        .map(|page| {
+            let rendered = mdbook_man::roff_to_string(page).unwrap();
+            rendered.lines().next().unwrap().to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn header_has_manual_of_section_by_default() {
+    let rendered = common::render("", CHAPTERS);
+
+    assert!(rendered.starts_with(".TH test 7 \"\" \"\" \"Miscellaneous\"\n"));
+}
+
+#[test]
+fn combined_page_name_is_configurable() {
+    let rendered = common::render("page-name = \"tool\"\nmanual = \"Tool Manual\"", CHAPTERS);
+
+    assert!(rendered.starts_with(".TH tool 7 \"\" \"\" \"Tool Manual\"\n"));
+}
+
+#[test]
+fn split_pages_share_manual_title() {
+    let headers = split_headers("manual = \"MyTool Manual\"");
+
+    assert_eq!(
+        headers,
+        [
+            ".TH mytool 7 \"\" \"\" \"MyTool Manual\"",
+            ".TH mytool\\-config 7 \"\" \"\" \"MyTool Manual\"",
+        ]
+    );
+}
+
+#[test]
+fn split_pages_are_named_after_their_chapter() {
+    let ctx = common::book(
+        "mytool",
+        "page-name = \"tool\"\nmanual = \"Tool Manual\"",
+        &[("Getting Started", "intro"), ("mytool", "main page")],
+    );
+    let pages = mdbook_man::mdbook_to_roff_chapters(&ctx).unwrap();
+    let headers: Vec<_> = pages
+        .iter()
+        .map(|page| mdbook_man::roff_to_string(page).unwrap())
+        .collect();
+
+    assert!(headers[0].starts_with(".TH \"Getting Started\" 7 \"\" \"\" \"Tool Manual\"\n"));
+    assert!(headers[1].starts_with(".TH mytool 7 \"\" \"\" \"Tool Manual\"\n"));
+}
+
+#[test]
+fn generation_comment_is_opt_in() {
+    let rendered = common::render("", CHAPTERS);
+    assert!(!rendered.contains("generated by"));
+
+    let ctx = common::book("mytool", "generation-comment = true", CHAPTERS);
+    let comment = format!(
+        ".\\\" generated by mdbook-man {} from mdbook {}\n.TH mytool 7",
+        env!("CARGO_PKG_VERSION"),
+        ctx.version
+    );
+    assert!(mdbook_man::mdbook_to_string(&ctx)
+        .unwrap()
+        .starts_with(&comment));
+
+    let ctx = common::book(
+        "mytool",
+        "generation-comment = true\nsplit-chapters = true",
+        CHAPTERS,
+    );
+    for page in mdbook_man::mdbook_to_roff_chapters(&ctx).unwrap() {
+        let rendered = mdbook_man::page_to_string(&ctx, &page).unwrap();
+        assert!(rendered.starts_with(".\\\" generated by mdbook-man"));
+    }
+}
+
+#[test]
+fn preprocessor_line_stays_first_with_generation_comment() {
+    let chapters = &[("Table", "| a |\n|---|\n| b |\n")];
+    let ctx = common::book("mytool", "generation-comment = true", chapters);
+    let rendered = mdbook_man::mdbook_to_string(&ctx).unwrap();
+
+    assert!(
+        rendered.starts_with("'\\\" t\n.\\\" generated by mdbook-man"),
+        "{}",
+        rendered
+    );
+}
+
+#[test]
+fn book_description_is_the_name_summary() {
+    let mut ctx = common::book("mytool", "", CHAPTERS);
+    ctx.config.book.description = Some("manage my tools".into());
+    let rendered = mdbook_man::mdbook_to_string(&ctx).unwrap();
+
+    assert!(rendered.starts_with(
+        ".TH mytool 7 \"\" \"\" \"Miscellaneous\"\n.SH NAME\n.P\nmytool \\- manage my tools\n"
+    ));
+}
+
+#[test]
+fn name_summary_falls_back_to_first_sentence() {
+    let rendered = common::render(
+        "",
+        &[("Intro", "Renders *books* as pages. More text follows.\n")],
+    );
+    assert!(rendered.contains(".SH NAME\n.P\ntest \\- Renders books as pages\n"));
+
+    let rendered = common::render("", &[("Intro", "```\ncode\n```\n")]);
+    assert!(rendered.contains(".SH NAME\n.P\ntest\n"));
+}
+
+#[test]
+fn authors_are_listed_one_per_line() {
+    let mut ctx = common::book("mytool", "", CHAPTERS);
+    ctx.config.book.authors = vec![
+        "Jane Doe <jane@example.com>".into(),
+        "John Roe".into(),
+        "<anon@example.com>".into(),
+    ];
+    let rendered = mdbook_man::mdbook_to_string(&ctx).unwrap();
+
+    let authors = rendered.split_once(".SH AUTHORS\n").unwrap().1;
+    assert_eq!(
+        authors,
+        ".P\n.MT jane@example.com\nJane Doe\n.ME\n.br\nJohn Roe\n.br\n.MT anon@example.com\n.ME\n"
+    );
+}
+
+#[test]
+fn authors_section_is_left_out_without_authors() {
+    assert!(!common::render("", CHAPTERS).contains("AUTHORS"));
+}
+
+#[test]
+fn manual_defaults_to_title_of_section() {
+    let rendered = common::render("section = 5", CHAPTERS);
+    assert!(rendered.starts_with(".TH test 5 \"\" \"\" \"File Formats\"\n"));
+
+    let rendered = common::render("section = 8\nmanual = \"Tool Manual\"", CHAPTERS);
+    assert!(rendered.starts_with(".TH test 8 \"\" \"\" \"Tool Manual\"\n"));
+
+    let rendered = common::render("section = 9", CHAPTERS);
+    assert!(rendered.starts_with(".TH test 9\n"));
+}
+
+#[test]
+fn split_pages_reference_each_other_in_their_section() {
+    let ctx = common::book(
+        "mytool",
+        "split-chapters = true\nsection = 1",
+        &[
+            ("mytool", "see [config](mytool-config.md)"),
+            ("mytool-config", "config"),
+        ],
+    );
+    let pages = mdbook_man::mdbook_to_roff_chapters(&ctx).unwrap();
+    let rendered = mdbook_man::roff_to_string(&pages[0]).unwrap();
+
+    assert!(
+        rendered.contains("(see \\fBmytool\\-config\\fR(1))"),
+        "{}",
+        rendered
+    );
+}
+
+#[test]
+fn date_and_source_are_header_fields() {
+    let rendered = common::render("date = \"May 2024\"\nsource = \"mytool 1.2\"", CHAPTERS);
+    assert!(rendered.starts_with(".TH test 7 \"May 2024\" \"mytool 1\\.2\" \"Miscellaneous\"\n"));
+
+    let rendered = common::render(
+        "date = \"May 2024\"\nsource = \"mytool 1.2\"\nmacro-package = \"mdoc\"",
+        CHAPTERS,
+    );
+    assert!(
+        rendered.starts_with(".Dd May 2024\n.Dt TEST 7\n.Os mytool 1\\.2\n"),
+        "{}",
+        rendered
+    );
+}
+
+const PROFILES: &str = "\
+section = 7
+date = \"May 2024\"
+
+[output.man.profiles.debian]
+section = 1
+manual = \"Debian Manual\"
+source = \"mytool 1.2-1\"
+";
+
+#[test]
+fn selected_profile_overrides_header_fields() {
+    let rendered = common::render(PROFILES, CHAPTERS);
+    assert!(rendered.starts_with(".TH test 7 \"May 2024\" \"\" \"Miscellaneous\"\n"));
+
+    let config = format!("profile = \"debian\"\n{}", PROFILES);
+    let rendered = common::render(&config, CHAPTERS);
+    assert!(
+        rendered.starts_with(".TH test 1 \"May 2024\" \"mytool 1\\.2\\-1\" \"Debian Manual\"\n"),
+        "{}",
+        rendered
+    );
+
+    let ctx = common::book("test", "profile = \"missing\"", CHAPTERS);
+    let err = mdbook_man::ManOutputConfiguration::load(&ctx).unwrap_err();
+    assert!(
+        err.to_string().contains("output.man.profiles.missing"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn hand_written_name_section_replaces_the_generated_one() {
+    let rendered = common::render(
+        "",
+        &[("Usage", "Run it.\n"), ("Name", "mytool \\- do things\n")],
+    );
+
+    assert_eq!(rendered.matches(".SH NAME").count(), 1, "{}", rendered);
+    assert!(rendered.starts_with(
+        ".TH test 7 \"\" \"\" \"Miscellaneous\"\n.SH NAME\n.P\nmytool \\- do things\n.SH Usage\n"
+    ), "{}", rendered);
+
+    let rendered = common::render(
+        "heading-as-title = true",
+        &[("Intro", "# NAME\n\nmytool \\- do things\n")],
+    );
+    assert_eq!(rendered.matches(".SH NAME").count(), 1, "{}", rendered);
+    assert!(!rendered.contains("test \\-"));
+}
+
+#[test]
+fn body_only_leaves_out_the_header() {
+    let rendered = common::render("body-only = true\ncheck = true", CHAPTERS);
+    assert!(!rendered.contains(".TH"));
+    assert!(rendered.starts_with(".SH NAME\n"), "{}", rendered);
+    assert!(rendered.contains(".SH mytool\\-config\n"));
+
+    let rendered = common::render("body-only = true\nmacro-package = \"mdoc\"", CHAPTERS);
+    assert!(rendered.starts_with(".Sh NAME\n"), "{}", rendered);
+
+    let ctx = common::book("mytool", "body-only = true", CHAPTERS);
+    for page in mdbook_man::mdbook_to_roff_chapters(&ctx).unwrap() {
+        let rendered = mdbook_man::page_to_string(&ctx, &page).unwrap();
+        assert!(rendered.starts_with(".SH "), "{}", rendered);
+    }
+}
+
+#[test]
+fn title_banner_tops_the_first_section() {
+    let rendered = common::render("", CHAPTERS);
+    assert!(!rendered.contains(".ce"));
+
+    let ctx = common::book("My Tool", "title-banner = true", CHAPTERS);
+    let rendered = mdbook_man::mdbook_to_string(&ctx).unwrap();
+    assert!(
+        rendered.contains(".SH mytool\n.ce 1\n\\fBMy Tool\\fR\n.P\nmain page\n"),
+        "{}",
+        rendered
+    );
+    assert_eq!(rendered.matches(".ce 1").count(), 1);
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use common::render;
+
+#[test]
+fn empty_headings_are_skipped() {
+    let rendered = render("", &[("Empty", "#\n\nText\n\n##   \n\nMore text\n")]);
+
+    assert!(!rendered.contains("\\fB\\fR"));
+    assert!(!rendered.contains("=="));
+    assert!(rendered.contains(".P\nText\n.P\nMore text"));
+}
+
+#[test]
+fn headings_are_rendered() {
+    let rendered = render("", &[("Heading", "# Title\n\nText\n")]);
+
+    assert!(rendered.contains("\\fBTitle\\fR\n.br\n=======\n"));
+}
+
+#[test]
+fn headings_keep_inline_content() {
+    let rendered = render("", &[("Heading", "## The `--flag` *option*\n\nText\n")]);
+
+    assert!(rendered.contains(
+        "\\fBThe\\fR \\fB`\\fR\\f(BI\\-\\-flag\\fR\\fB`\\fR \\f(BIoption\\fR\n.br\n===================\n"
+    ));
+}
+
+const DUPLICATED: &[(&str, &str)] = &[("Install", "# Installing the tool\n\nRun it.\n\n# Later\n")];
+
+#[test]
+fn leading_heading_is_rendered_in_the_body_by_default() {
+    let rendered = render("", DUPLICATED);
+
+    assert!(rendered.contains(".SH Install\n"));
+    assert!(rendered.contains("\\fBInstalling the tool\\fR"));
+}
+
+#[test]
+fn leading_heading_can_become_the_section_title() {
+    let rendered = render("heading-as-title = true", DUPLICATED);
+
+    assert!(
+        rendered.contains(".SH \"Installing the tool\"\n"),
+        "{}",
+        rendered
+    );
+    assert!(!rendered.contains("\\fBInstalling the tool\\fR"));
+    assert!(!rendered.contains(".SH Install\n"));
+    // only the heading starting the chapter is taken
+    assert!(rendered.contains("\\fBLater\\fR"));
+}
+
+#[test]
+fn code_in_headings_is_styled_like_in_the_body() {
+    let chapters = &[(
+        "Config",
+        "# The `config` file\n\nEdit `config`.\n\n## The `x` option\n",
+    )];
+
+    let rendered = render("heading-as-title = true", chapters);
+    assert!(
+        rendered.contains(".SH \"The `\\fIconfig\\fP` file\"\n"),
+        "{}",
+        rendered
+    );
+    assert!(rendered.contains("Edit `\\fIconfig\\fR`\\."));
+    assert!(rendered.contains("\\fBThe\\fR \\fB`\\fR\\f(BIx\\fR\\fB`\\fR \\fBoption\\fR"));
+
+    let rendered = render(
+        "heading-as-title = true\ninline-code-style = \"italic\"",
+        chapters,
+    );
+    assert!(
+        rendered.contains(".SH \"The \\fIconfig\\fP file\"\n"),
+        "{}",
+        rendered
+    );
+    assert!(rendered.contains("Edit \\fIconfig\\fR\\."));
+    assert!(
+        rendered.contains("\\fBThe\\fR \\f(BIx\\fR \\fBoption\\fR"),
+        "{}",
+        rendered
+    );
+}
@@ -0,0 +1,310 @@
+mod common;
+
+use common::render;
+
+/// Renders `markdown` as the only chapter and returns its section without the page header.
+fn inline(markdown: &str) -> String {
+    let rendered = render("", &[("Inline", markdown)]);
+    rendered.split_once(".SH Inline\n").unwrap().1.to_string()
+}
+
+#[test]
+fn emphasis_inside_strong() {
+    let rendered = inline("**bold *both* more**\n");
+
+    assert!(rendered.contains("\\fBbold\\fR \\f(BIboth\\fR \\fBmore\\fR"));
+}
+
+#[test]
+fn strong_inside_emphasis() {
+    let rendered = inline("*italic **both** more*\n");
+
+    assert!(rendered.contains("\\fIitalic\\fR \\f(BIboth\\fR \\fImore\\fR"));
+}
+
+#[test]
+fn style_ends_with_its_span() {
+    let rendered = inline("plain **bold** plain\n");
+
+    assert!(rendered.contains("plain \\fBbold\\fR plain"));
+}
+
+#[test]
+fn emphasis_inside_link() {
+    let rendered = inline("see [*docs*](https://example.com) now\n");
+
+    assert!(rendered.contains("\\fIdocs\\fR\n.UR https://example\\.com\n.UE\nnow"));
+}
+
+#[test]
+fn link_inside_emphasis() {
+    let rendered = inline("*see [docs](https://example.com) now*\n");
+
+    assert!(
+        rendered.contains("\\fIsee\\fR \\fIdocs\\fR\n.UR https://example\\.com\n.UE\n\\fInow\\fR")
+    );
+}
+
+#[test]
+fn link_with_code_text() {
+    let rendered = inline("use [`foo`](https://example.com/foo) here\n");
+
+    assert!(rendered.contains("`\\fIfoo\\fR`\n.UR https://example\\.com/foo\n.UE\nhere"));
+}
+
+#[test]
+fn image_renders_alt_text() {
+    let rendered = inline("see ![the logo](logo.png) here\n");
+
+    assert!(rendered.contains("see the logo\n.UR logo\\.png\n.UE\nhere"));
+}
+
+#[test]
+fn autolink_renders_address_once() {
+    let rendered = inline("visit <https://example.com> now\n");
+
+    assert!(rendered.contains("visit \n.UR https://example\\.com\n.UE\nnow"));
+    assert_eq!(rendered.matches("example").count(), 1);
+}
+
+#[test]
+fn multi_line_paragraph_is_joined() {
+    let rendered = inline("first line\nsecond *line*\nthird line\n");
+
+    assert_eq!(rendered.matches(".P").count(), 1);
+    assert!(rendered.contains(".P\nfirst line second \\fIline\\fR third line"));
+}
+
+#[test]
+fn soft_breaks_as_hard_breaks() {
+    let markdown = "first line\nsecond line\n";
+
+    let filled = inline(markdown);
+    let kept = render("hard-breaks = true", &[("Inline", markdown)]);
+
+    assert!(filled.contains(".P\nfirst line second line"));
+    assert!(kept.contains(".P\nfirst line\n.br\nsecond line"));
+}
+
+const LINKED: &[(&str, &str)] = &[
+    ("Intro", "see [the setup](./install.md#setup) first\n"),
+    (
+        "Install",
+        "run [make](https://example.com/make.md) and [gone](missing.md)\n",
+    ),
+];
+
+#[test]
+fn chapter_links_reference_sections() {
+    let rendered = render("", LINKED);
+
+    assert!(rendered.contains("see the setup (see \\fIInstall\\fR) first"));
+    assert!(rendered.contains(".UR https://example\\.com/make\\.md\n.UE\nand gone"));
+    assert!(!rendered.contains("install\\.md"));
+}
+
+#[test]
+fn chapter_links_reference_the_final_section_titles() {
+    let chapters = &[
+        (
+            "Intro",
+            "see [the setup](./install.md) and [more](usage-2.md)\n",
+        ),
+        ("Install", "# Installing the tool\n\nrun make\n"),
+        ("Usage", "first\n"),
+        ("Usage 2", "# Usage\n\nsecond\n"),
+    ];
+    let rendered = render("heading-as-title = true", chapters);
+
+    assert!(
+        rendered.contains(".SH \"Installing the tool\"\n"),
+        "{}",
+        rendered
+    );
+    assert!(rendered.contains(".SH \"Usage (2)\"\n"), "{}", rendered);
+    assert!(
+        rendered.contains(
+            "see the setup (see \\fIInstalling the tool\\fR) and more (see \\fIUsage (2)\\fR)"
+        ),
+        "{}",
+        rendered
+    );
+}
+
+#[test]
+fn chapter_links_reference_pages_when_split() {
+    let ctx = common::book("test", "", LINKED);
+    let pages = mdbook_man::mdbook_to_roff_chapters(&ctx).unwrap();
+    let intro = mdbook_man::roff_to_string(&pages[0]).unwrap();
+
+    assert!(intro.contains("see the setup (see \\fBInstall\\fR(7)) first"));
+}
+
+#[test]
+fn local_images_are_embedded_with_pspic() {
+    let root = std::env::temp_dir().join(format!("mdbook-man-pspic-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("src").join("img")).unwrap();
+    std::fs::write(root.join("src").join("img").join("logo.eps"), "%!PS").unwrap();
+
+    let chapters = &[(
+        "Images",
+        "see ![logo](img/logo.eps) and ![gone](img/gone.eps) here\n",
+    )];
+    let mut ctx = common::book("test", "pspic-images = true", chapters);
+    ctx.root = root.clone();
+    let rendered = mdbook_man::mdbook_to_string(&ctx).unwrap();
+
+    let logo = root.join("src").join("img").join("logo.eps");
+    assert!(rendered.contains(&format!(
+        "see \n.PSPIC \"{}\"\nand gone\n.UR img/gone\\.eps",
+        logo.display()
+    )));
+
+    ctx.config.set("output.man.pspic-images", false).unwrap();
+    let rendered = mdbook_man::mdbook_to_string(&ctx).unwrap();
+    assert!(!rendered.contains(".PSPIC"));
+    assert!(rendered.contains("see logo\n.UR img/logo\\.eps"));
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn strikethrough_is_bracketed_by_default() {
+    let rendered = inline("an ~~old~~ word\n");
+
+    assert!(rendered.contains("an [\\-old\\-] word"), "{}", rendered);
+}
+
+#[test]
+fn strikethrough_can_be_overstruck() {
+    let rendered = render(
+        "strikethrough-style = \"overstrike\"",
+        &[("Inline", "an ~~old v1~~ word\n")],
+    );
+
+    assert!(
+        rendered.contains("an \\o'o\\(sl'\\o'l\\(sl'\\o'd\\(sl' \\o'v\\(sl'\\o'1\\(sl' word"),
+        "{}",
+        rendered
+    );
+}
+
+#[test]
+fn emphasis_spans_across_inline_code() {
+    let rendered = inline("**foo `bar` baz** and *x `y` z*\n");
+
+    assert!(
+        rendered.contains("\\fBfoo\\fR \\fB`\\fR\\f(BIbar\\fR\\fB`\\fR \\fBbaz\\fR and "),
+        "{}",
+        rendered
+    );
+    assert!(rendered.contains("\\fIx\\fR \\fI`\\fR\\fIy\\fR\\fI`\\fR \\fIz\\fR"));
+}
+
+#[test]
+fn image_inside_link_references_the_link() {
+    let rendered = inline("[![the logo](logo.png)](https://example.com) and [x ![](y.png)](z)\n");
+
+    assert!(
+        rendered.contains("the logo\n.UR https://example\\.com\n.UE\nand x \n.UR z\n.UE"),
+        "{}",
+        rendered
+    );
+    assert!(!rendered.contains("png"));
+}
+
+#[test]
+fn whitespace_is_moved_out_of_styled_runs() {
+    let rendered = inline("**bold** and *x* `` a  b ``\n");
+    assert!(
+        rendered.contains("\\fBbold\\fR and \\fIx\\fR `\\fIa  b\\fR`"),
+        "{}",
+        rendered
+    );
+
+    let rendered = inline("*one **two** three*\n");
+    assert!(rendered.contains("\\fIone\\fR \\f(BItwo\\fR \\fIthree\\fR"));
+
+    let rendered = render(
+        "keep-styled-whitespace = true",
+        &[("Inline", "*one **two** three*\n")],
+    );
+    assert!(rendered.contains("\\fIone \\fR\\f(BItwo\\fR\\fI three\\fR"));
+}
+
+#[test]
+fn reference_images_render_like_inline_images() {
+    let rendered = inline(
+        "![inline](a.png) ![full][r] ![collapsed][] ![shortcut]\n\n[r]: b.png\n[collapsed]: c.png\n[shortcut]: d.png\n",
+    );
+
+    assert!(
+        rendered.contains("inline\n.UR a\\.png\n.UE\nfull\n.UR b\\.png\n.UE\ncollapsed\n.UR c\\.png\n.UE\nshortcut\n.UR d\\.png\n.UE"),
+        "{}",
+        rendered
+    );
+}
+
+#[test]
+fn unresolved_reference_images_render_alt_text() {
+    let rendered = inline("see ![missing][nope], ![gone][] and ![lost] [here]\n");
+
+    assert!(
+        rendered.contains("see missing, gone and lost [here]"),
+        "{}",
+        rendered
+    );
+}
+
+#[test]
+fn non_ascii_characters_are_escaped_for_the_target() {
+    let chapters = &[("Inline", "Wait — naïve\n\n```\ncafé\n```\n")];
+
+    let rendered = render("", chapters);
+    assert!(rendered.contains("Wait — naïve"));
+
+    let rendered = render("target = \"mandoc\"", chapters);
+    assert!(
+        rendered.contains("Wait \\[u2014] na\\[u00EF]ve"),
+        "{}",
+        rendered
+    );
+    assert!(rendered.contains("caf\\[u00E9]"));
+
+    let rendered = render("target = \"troff\"", chapters);
+    assert!(
+        rendered.contains("Wait \\(em na\\[u00EF]ve"),
+        "{}",
+        rendered
+    );
+    assert!(rendered.is_ascii());
+}
+
+const LONG_LINKS: &str = "Read [the guide](https://example.com/docs/guide/getting-started.html) or <https://example.com/docs/reference/index.html>, see [home](https://example.com).\n";
+
+#[test]
+fn long_urls_are_shortened_and_listed_in_see_also() {
+    let rendered = render("", &[("Links", LONG_LINKS)]);
+    assert!(rendered.contains(".UR https://example\\.com/docs/guide/getting\\-started\\.html\n"));
+    assert!(!rendered.contains("SEE ALSO"));
+
+    let rendered = render("truncate-urls = 20", &[("Links", LONG_LINKS)]);
+    let (body, see_also) = rendered.split_once(".SH \"SEE ALSO\"\n").unwrap();
+    assert!(
+        body.contains(
+            "Read the guide \\(lahttps://example\\.com/\\.\\.\\.\\(ra or https://example\\.com/\\.\\.\\., see home\n.UR https://example\\.com\n.UE\n"
+        ),
+        "{}",
+        rendered
+    );
+    assert!(!body.contains("getting"));
+    assert_eq!(
+        see_also,
+        ".P\n.UR https://example\\.com/docs/guide/getting\\-started\\.html\n.UE\n.br\n.UR https://example\\.com/docs/reference/index\\.html\n.UE\n"
+    );
+
+    let ctx = common::book("test", "truncate-urls = 20", &[("Links", LONG_LINKS)]);
+    let pages = mdbook_man::mdbook_to_roff_chapters(&ctx).unwrap();
+    let rendered = mdbook_man::roff_to_string(&pages[0]).unwrap();
+    assert!(rendered.contains(".SH \"SEE ALSO\"\n.P\n.UR https://example\\.com/docs/guide/"));
+}
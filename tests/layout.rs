@@ -0,0 +1,185 @@
+mod common;
+
+use common::render;
+
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+const CHAPTERS: &[(&str, &str)] = &[("One", "first"), ("Two", "second")];
+
+#[test]
+fn no_layout_requests_by_default() {
+    let rendered = render("", CHAPTERS);
+
+    assert!(!rendered.contains(".ll"));
+    assert!(!rendered.contains(".na"));
+}
+
+#[test]
+fn ragged_right_emits_no_adjust() {
+    let rendered = render("ragged-right = true", CHAPTERS);
+
+    assert!(rendered.starts_with(".TH test 7 \"\" \"\" \"Miscellaneous\"\n.SH NAME\n.na\n"));
+    assert_eq!(rendered.matches(".na").count(), 1);
+}
+
+#[test]
+fn line_length_is_set_on_every_split_page() {
+    let ctx = common::book("test", "line-length = 72", CHAPTERS);
+    let pages = mdbook_man::mdbook_to_roff_chapters(&ctx).unwrap();
+
+    for page in pages {
+        let rendered = mdbook_man::roff_to_string(&page).unwrap();
+        assert!(rendered.contains(".nr LL 72n\n.ll 72n\n"));
+    }
+}
+
+#[test]
+fn hyphenation_is_kept_by_default() {
+    assert!(!render("", CHAPTERS).contains(".nh"));
+}
+
+#[test]
+fn hyphenation_can_be_disabled() {
+    let rendered = render("no-hyphenation = true", CHAPTERS);
+    assert!(rendered.starts_with(".TH test 7 \"\" \"\" \"Miscellaneous\"\n.SH NAME\n.nh\n"));
+
+    let ctx = common::book("test", "no-hyphenation = true", CHAPTERS);
+    for page in mdbook_man::mdbook_to_roff_chapters(&ctx).unwrap() {
+        assert!(mdbook_man::roff_to_string(&page)
+            .unwrap()
+            .contains("\n.nh\n"));
+    }
+}
+
+const BLANK: &[(&str, &str)] = &[("Blank", "first\n\n&nbsp;\n\n\n&nbsp;\n\n&nbsp;\n\nsecond\n")];
+
+#[test]
+fn consecutive_empty_paragraphs_are_collapsed() {
+    let rendered = render("", BLANK);
+
+    assert!(
+        rendered.contains(".P\nfirst\n.P\n\u{a0}\n.P\nsecond"),
+        "{}",
+        rendered
+    );
+    assert_eq!(rendered.matches('\u{a0}').count(), 1);
+    assert!(rendered.contains("second"));
+}
+
+#[test]
+fn empty_paragraphs_can_be_kept() {
+    let rendered = render("keep-empty-paragraphs = true", BLANK);
+
+    assert_eq!(rendered.matches('\u{a0}').count(), 3);
+}
+
+const KEPT: &[(&str, &str)] = &[("Kept", "# Heading\n\ntext\n\n```\na\nb\n```\n")];
+
+#[test]
+fn headings_and_examples_are_kept_together_when_enabled() {
+    let rendered = render("keep-together = true", KEPT);
+
+    assert!(
+        rendered.contains(".ne 5\n.br\n\n.br\n\\fBHeading\\fR"),
+        "{}",
+        rendered
+    );
+    assert!(rendered.contains("text\n.ne 3\n.RS\n"));
+    assert!(!render("", KEPT).contains(".ne"));
+}
+
+#[test]
+#[ignore = "needs nroff, run with `cargo test -- --ignored`"]
+fn nroff_wraps_at_the_line_length() {
+    let text = "word ".repeat(200);
+    let chapters = &[("One", text.as_str()), ("Two", text.as_str())];
+    let rendered = render("line-length = 40", chapters);
+
+    let mut child = Command::new("nroff")
+        .args(["-man", "-Tascii"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("nroff is installed");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(rendered.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    let text = String::from_utf8_lossy(&output.stdout);
+
+    assert!(text.lines().any(|line| line.contains("word")));
+    for line in text.lines().filter(|line| line.contains("word")) {
+        assert!(line.trim_end().chars().count() <= 40, "{:?}", line);
+    }
+}
+
+#[test]
+fn long_words_can_be_broken() {
+    let url = format!("https://example.com/{}", "a".repeat(180));
+    let text = format!("See {} for more.\n", url);
+    let chapters = &[("One", text.as_str())];
+
+    let rendered = render("line-length = 60", chapters);
+    assert!(!rendered.contains("\\:"));
+
+    let rendered = render("line-length = 60\nbreak-long-words = 50", chapters);
+    let line = rendered
+        .lines()
+        .find(|line| line.starts_with("See "))
+        .unwrap();
+    assert_eq!(
+        line.replace("\\:", "").replace("\\.", "."),
+        format!("See {} for more.", url)
+    );
+    for run in line
+        .split(|c: char| c.is_whitespace())
+        .flat_map(|word| word.split("\\:"))
+    {
+        assert!(run.replace("\\.", ".").chars().count() <= 50, "{}", line);
+    }
+}
+
+#[test]
+fn escapes_are_not_split_by_break_points() {
+    let rendered = render(
+        "strikethrough-style = \"overstrike\"\nbreak-long-words = 5",
+        &[("One", "~~abcdefgh~~ `x\\y` **longer-word**\n")],
+    );
+    let line = rendered
+        .lines()
+        .find(|line| line.starts_with("\\o"))
+        .unwrap();
+
+    assert_eq!(
+        line.replace("\\:", ""),
+        "\\o'a\\(sl'\\o'b\\(sl'\\o'c\\(sl'\\o'd\\(sl'\\o'e\\(sl'\\o'f\\(sl'\\o'g\\(sl'\\o'h\\(sl' `\\fIx\\ey\\fR` \\fBlonger\\-word\\fR"
+    );
+    assert!(line.contains("\\o'e\\(sl'\\:\\o'f\\(sl'"), "{}", line);
+    for part in line.split("\\:") {
+        assert_eq!(part.matches('\'').count() % 2, 0, "{}", line);
+    }
+}
+
+#[test]
+fn zero_break_width_is_a_config_error() {
+    let ctx = common::book("test", "break-long-words = 0", &[("One", "text")]);
+    assert!(matches!(
+        mdbook_man::mdbook_to_string(&ctx),
+        Err(mdbook_man::Error::Config { .. })
+    ));
+
+    let cfg = mdbook_man::ManOutputConfiguration {
+        break_long_words: Some(0),
+        ..Default::default()
+    };
+    assert!(matches!(
+        mdbook_man::render_markdown("text", &cfg),
+        Err(mdbook_man::Error::Config { .. })
+    ));
+}
@@ -0,0 +1,81 @@
+mod common;
+
+use mdbook_man::{lint_page, Error};
+
+fn messages(rendered: &str) -> Vec<String> {
+    lint_page(rendered)
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[test]
+fn rendered_pages_pass() {
+    let ctx = common::fixture_book("book");
+    let rendered = mdbook_man::mdbook_to_string(&ctx).unwrap();
+
+    assert_eq!(messages(&rendered), Vec::<String>::new());
+}
+
+#[test]
+fn missing_header_is_flagged() {
+    assert_eq!(
+        messages(".SH Intro\ntext"),
+        ["line 1: page doesn't start with a `.TH` header"]
+    );
+}
+
+#[test]
+fn unbalanced_blocks_are_flagged() {
+    let rendered = ".TH test 7\n.SH Intro\n.RS\n.TS\nl.\ncell\n.RE\n.SH Outro\n.TE";
+
+    assert_eq!(
+        messages(rendered),
+        [
+            "line 4 in section `Intro`: `.TS` is never closed",
+            "line 9 in section `Outro`: `.TE` without a preceding `.TS`",
+        ]
+    );
+}
+
+#[test]
+fn stray_control_characters_are_flagged() {
+    let rendered = ".TH test 7\n.SH Intro\n'quoted\nbell \u{7}\nmarker \u{FDD0}.br";
+
+    assert_eq!(
+        messages(rendered),
+        [
+            "line 3 in section `Intro`: text line starts with the control character `'`",
+            "line 4 in section `Intro`: stray control character '\\u{7}'",
+            "line 5 in section `Intro`: stray control character '\\u{fdd0}'",
+        ]
+    );
+}
+
+#[test]
+fn requests_with_leading_space_are_handled() {
+    assert_eq!(messages(".TH test 7\n. é\n.  RS"), Vec::<String>::new());
+}
+
+#[test]
+fn check_is_only_run_when_enabled() {
+    let chapters = &[("Intro", "'quoted text\n")];
+
+    let ctx = common::book("test", "", chapters);
+    assert!(mdbook_man::mdbook_to_string(&ctx).is_ok());
+
+    let ctx = common::book("test", "check = true", chapters);
+    match mdbook_man::mdbook_to_string(&ctx) {
+        Err(Error::Lint(issues)) => assert_eq!(issues[0].section.as_deref(), Some("Intro")),
+        other => panic!("expected a lint error, got {:?}", other),
+    }
+}
+
+#[test]
+fn private_use_characters_pass() {
+    let chapters = &[("Intro", "branch \u{E0A0} main and \u{FDD0}marker\n")];
+    let ctx = common::book("test", "check = true", chapters);
+    let rendered = mdbook_man::mdbook_to_string(&ctx).unwrap();
+
+    assert!(rendered.contains("branch \u{E0A0} main and \u{FFFD}marker\n"));
+}
@@ -0,0 +1,260 @@
+mod common;
+
+use common::render;
+
+#[test]
+fn option_lists_render_as_tagged_paragraphs() {
+    let rendered = render(
+        "",
+        &[(
+            "Options",
+            "- `--verbose`: enable logging\n- `-o <file>`: write output to *file*\n",
+        )],
+    );
+
+    assert!(rendered.contains(".TP\n\\fB\\-\\-verbose\\fR\nenable logging\n"));
+    assert!(rendered.contains(".TP\n\\fB\\-o <file>\\fR\nwrite output to \\fIfile\\fR\n"));
+    assert!(!rendered.contains("`"));
+}
+
+#[test]
+fn option_list_items_keep_their_blocks() {
+    let rendered = render(
+        "",
+        &[(
+            "Options",
+            "- `--verbose`: enable *verbose* logging\n\n  More details.\n\n  ```\n  code\n  ```\n",
+        )],
+    );
+
+    assert!(rendered.contains(".TP\n\\fB\\-\\-verbose\\fR\nenable \\fIverbose\\fR logging\n"));
+    assert!(rendered.contains(".IP\nMore details\\.\n"));
+    assert!(rendered.contains(".EX\ncode\n"));
+    assert!(!rendered.contains(".P\nMore details"));
+}
+
+#[test]
+fn blocks_after_a_list_end_it() {
+    let rendered = render("", &[("List", "- one\n- two\n\n```\ncode\n```\n")]);
+
+    assert!(
+        rendered.contains("two\n.PD 0\n.P\n.PD\n.RS\n"),
+        "{}",
+        rendered
+    );
+
+    let rendered = render("", &[("List", "- one\n\n  ```\n  code\n  ```\n")]);
+    assert!(!rendered.contains(".PD"));
+}
+
+#[test]
+fn ordinary_list_items_are_left_alone() {
+    let rendered = render("", &[("List", "- plain item\n- `code` without a colon\n")]);
+
+    assert!(!rendered.contains(".TP"));
+    assert!(rendered.contains("plain item"));
+    assert!(rendered.contains("`\\fIcode\\fR`"));
+}
+
+const TASKS: &str = "- [x] **done** with `code`\n- [ ] todo\n";
+
+#[test]
+fn task_items_render_default_markers() {
+    let rendered = render("", &[("Tasks", TASKS)]);
+
+    assert!(rendered.contains(".P\n[x] \\fBdone\\fR with `\\fIcode\\fR`\n"));
+    assert!(rendered.contains(".P\n[ ] todo"));
+}
+
+#[test]
+fn task_item_markers_are_configurable() {
+    let rendered = render(
+        "task-checked = \"☑\"\ntask-unchecked = \"☐\"",
+        &[("Tasks", TASKS)],
+    );
+
+    assert!(rendered.contains(".P\n☑ \\fBdone\\fR with"));
+    assert!(rendered.contains(".P\n☐ todo"));
+}
+
+const DEFINITIONS: &str = "\
+Options of the tool:
+
+output
+    Directory the pages are written to.
+
+format
+: Format of the pages.
+
+This is an ordinary sentence that was
+wrapped in the source.
+";
+
+#[test]
+fn definitions_are_detected() {
+    let rendered = render("detect-definitions = true", &[("Definitions", DEFINITIONS)]);
+
+    assert!(rendered.contains(".TP\n\\fBoutput\\fR\nDirectory the pages are written to\\."));
+    assert!(rendered.contains(".TP\n\\fBformat\\fR\nFormat of the pages\\."));
+    assert!(rendered.contains(".P\nThis is an ordinary sentence that was wrapped in the source\\."));
+    assert_eq!(rendered.matches(".TP").count(), 2);
+}
+
+#[test]
+fn indented_sentences_are_not_definitions() {
+    let chapter = "The quick brown fox\n  jumps over the lazy dog.\n\nWrapping\n  lines is hard.\n";
+    let rendered = render("detect-definitions = true", &[("Prose", chapter)]);
+
+    assert!(!rendered.contains(".TP"), "{}", rendered);
+    assert!(rendered.contains(".P\nThe quick brown fox jumps over the lazy dog\\."));
+    assert!(rendered.contains(".P\nWrapping lines is hard\\."));
+}
+
+#[test]
+fn definitions_are_not_detected_by_default() {
+    let rendered = render("", &[("Definitions", DEFINITIONS)]);
+
+    assert!(!rendered.contains(".TP"));
+    assert!(rendered.contains(".P\noutput Directory the pages are written to\\."));
+}
+
+fn nested_list(depth: usize) -> String {
+    (0..depth)
+        .map(|level| format!("{}- level{}\n", "  ".repeat(level), level))
+        .collect()
+}
+
+#[test]
+fn deeply_nested_list_is_truncated() {
+    let markdown = nested_list(1000);
+
+    let rendered = render("", &[("Nested", &markdown)]);
+
+    assert!(rendered.contains("level0"));
+    assert!(!rendered.contains("level999"));
+}
+
+#[test]
+fn max_depth_is_configurable() {
+    let markdown = nested_list(10);
+
+    let rendered = render("max-depth = 8", &[("Nested", &markdown)]);
+
+    assert!(rendered.contains("level1"));
+    assert!(!rendered.contains("level9"));
+}
+
+const INTERRUPTED: &str = "1. first\n2. second\n\nA note between the steps.\n\n1. third\n";
+
+#[test]
+fn ordered_lists_restart_numbering_by_default() {
+    let rendered = render("", &[("Steps", INTERRUPTED)]);
+
+    assert!(rendered.contains(".IP 1\\. 4\nfirst\n"), "{}", rendered);
+    assert!(rendered.contains(".IP 2\\. 4\nsecond\n"));
+    assert!(rendered.contains(".IP 1\\. 4\nthird"));
+}
+
+#[test]
+fn ordered_list_numbering_can_continue() {
+    let rendered = render("continue-ordered-lists = true", &[("Steps", INTERRUPTED)]);
+
+    assert!(rendered.contains(".IP 2\\. 4\nsecond\n"), "{}", rendered);
+    assert!(rendered.contains(".IP 3\\. 4\nthird"));
+    assert!(!rendered.contains(".IP 1\\. 4\nthird"));
+
+    let rendered = render(
+        "continue-ordered-lists = true",
+        &[("Steps", "1. first\n\n# Next\n\n1. again\n")],
+    );
+    assert!(rendered.contains(".IP 1\\. 4\nagain"));
+}
+
+#[test]
+fn continuation_paragraphs_are_indented_under_their_item() {
+    let rendered = render(
+        "",
+        &[(
+            "Steps",
+            "1. first\n\n   more about first\n2. second\n\n- bullet\n\n  more about bullet\n",
+        )],
+    );
+
+    assert!(
+        rendered.contains(".IP 1\\. 4\nfirst\n.IP \"\" 4\nmore about first\n"),
+        "{}",
+        rendered
+    );
+    assert!(rendered.contains(".IP 2\\. 4\nsecond\n"));
+    assert!(rendered.contains(".IP \\(bu 4\nbullet\n.IP \"\" 4\nmore about bullet\n"));
+}
+
+#[test]
+fn item_text_lines_up_after_the_widest_marker() {
+    let list: String = (1..=12)
+        .map(|i| format!("{}. step {}\n\n    more about {}\n\n", i, i, i))
+        .collect();
+    let rendered = render("", &[("Steps", &list)]);
+
+    let indents: Vec<_> = rendered
+        .lines()
+        .filter(|line| line.starts_with(".IP"))
+        .map(|line| line.rsplit(' ').next().unwrap())
+        .collect();
+    assert_eq!(indents.len(), 24);
+    assert!(indents.iter().all(|indent| *indent == "4"), "{:?}", indents);
+
+    let rendered = render(
+        "",
+        &[("Steps", "998. one\n\n     more\n\n999. two\n1000. three\n")],
+    );
+    assert!(rendered.contains(
+        ".IP 998\\. 6\none\n.IP \"\" 6\nmore\n\n.IP 999\\. 6\ntwo\n.IP 1000\\. 6\nthree"
+    ));
+}
+
+#[test]
+fn item_indent_is_configurable() {
+    let list = "1. first item\n\n   wrapped under the text\n\n* bullet\n\n  continued\n";
+
+    let rendered = render("list-indent = 8", &[("Steps", list)]);
+    assert!(
+        rendered.contains(".IP 1\\. 8\nfirst item\n.IP \"\" 8\nwrapped under the text\n"),
+        "{}",
+        rendered
+    );
+    assert!(rendered.contains(".IP \"\" 8\ncontinued"), "{}", rendered);
+
+    let rendered = render("list-indent = 2", &[("Steps", "9. one\n10. two\n")]);
+    assert!(
+        rendered.contains(".IP 9\\. 4\none\n.IP 10\\. 4\ntwo"),
+        "{}",
+        rendered
+    );
+}
+
+const GLOSSARY: &[(&str, &str)] = &[
+    ("Usage", "Run the tool.\n"),
+    (
+        "Glossary",
+        "* **page**: a rendered chapter\n* **book**\n  * the source of the pages\n    * written in markdown\n* [site](https://example.com)\n",
+    ),
+];
+
+#[test]
+fn list_only_chapter_is_an_indented_list_under_its_heading() {
+    let list = ".SH Glossary\n.IP \\(bu 4\n\\fBpage\\fR: a rendered chapter\n.IP \\(bu 4\n\\fBbook\\fR\n.RS\n.IP \\(bu 4\nthe source of the pages\n.RS\n.IP \\(bu 4\nwritten in markdown\n.RE\n.RE\n.IP \\(bu 4\nsite\n";
+
+    let rendered = render("", GLOSSARY);
+    assert!(rendered.contains(list), "{}", rendered);
+
+    let ctx = common::book("test", "", GLOSSARY);
+    let pages = mdbook_man::mdbook_to_roff_chapters(&ctx).unwrap();
+    let rendered = mdbook_man::roff_to_string(&pages[1]).unwrap();
+    assert!(
+        rendered.starts_with(".TH Glossary 7 \"\" \"\" \"Miscellaneous\"\n"),
+        "{}",
+        rendered
+    );
+    assert!(rendered.contains(list), "{}", rendered);
+}
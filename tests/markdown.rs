@@ -0,0 +1,84 @@
+use mdbook_man::{render_markdown, CodeBlockStyle, MacroPackage, ManOutputConfiguration};
+
+const TEXT: &str = "Some *text*.\n\n```\ncode\n```\n\nFirst line\nsecond line\n";
+
+#[test]
+fn markdown_renders_without_a_book() {
+    let rendered = render_markdown(TEXT, &ManOutputConfiguration::default()).unwrap();
+
+    assert!(
+        rendered.starts_with(".TH untitled 7 \"\" \"\" \"Miscellaneous\"\n.SH DESCRIPTION\n"),
+        "{}",
+        rendered
+    );
+    assert!(rendered.contains("Some \\fItext\\fR\\."));
+    assert!(rendered.contains(".EX\ncode\n"));
+    assert!(rendered.contains("First line second line"));
+}
+
+#[test]
+fn markdown_uses_header_fields_of_config() {
+    let cfg = ManOutputConfiguration {
+        page_name: Some("tool".into()),
+        manual: Some("Tool Manual".into()),
+        line_length: Some(72),
+        ..Default::default()
+    };
+    let rendered = render_markdown(TEXT, &cfg).unwrap();
+
+    assert!(rendered.starts_with(
+        ".TH tool 7 \"\" \"\" \"Tool Manual\"\n.SH DESCRIPTION\n.nr LL 72n\n.ll 72n\n"
+    ));
+}
+
+#[test]
+fn markdown_honors_rendering_options() {
+    let cfg = ManOutputConfiguration {
+        hard_breaks: true,
+        omit_code_blocks: true,
+        ..Default::default()
+    };
+    let rendered = render_markdown(TEXT, &cfg).unwrap();
+
+    assert!(!rendered.contains("code"));
+    assert!(rendered.contains("First line\n.br\nsecond line"));
+
+    let cfg = ManOutputConfiguration {
+        code_block_style: CodeBlockStyle::Verbatim,
+        ..Default::default()
+    };
+    let rendered = render_markdown(TEXT, &cfg).unwrap();
+    assert!(rendered.contains("code\n"));
+    assert!(!rendered.contains(".EX"));
+
+    let cfg = ManOutputConfiguration {
+        page_name: Some("tool".into()),
+        macro_package: MacroPackage::Mdoc,
+        ..Default::default()
+    };
+    let rendered = render_markdown(TEXT, &cfg).unwrap();
+    assert!(rendered
+        .starts_with(".Dd $Mdocdate$\n.Dt TOOL 7\n.Os\n.Sh NAME\n.Nm tool\n.Nd Some text\n"));
+}
+
+#[test]
+fn markdown_nodes_are_converted_from_node_values() {
+    use mdbook_man::{MarkdownNode, NodeValue};
+
+    assert_eq!(
+        MarkdownNode::from(&NodeValue::Paragraph),
+        MarkdownNode::Paragraph
+    );
+    assert_eq!(
+        MarkdownNode::from(&NodeValue::BlockQuote),
+        MarkdownNode::BlockQuote
+    );
+    assert_eq!(
+        MarkdownNode::from(&NodeValue::TableRow(true)),
+        MarkdownNode::TableRow
+    );
+    assert_eq!(
+        MarkdownNode::from(&NodeValue::Document),
+        MarkdownNode::Empty
+    );
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+const CHAPTER: &str = "\
+Some *text* with a [link](https://example.com).
+
+```rust
+fn main() {}
+```
+
+* `--verbose`: print more
+* `--quiet`: print less
+
+```
+after the list
+```
+
+- one
+- two
+
+| a | b |
+|---|---|
+| 1 | 2 |
+
+Closing paragraph.
+";
+
+fn mdoc() -> String {
+    common::render("macro-package = \"mdoc\"", &[("Usage", CHAPTER)])
+}
+
+#[test]
+fn header_is_translated_to_prologue() {
+    let rendered = mdoc();
+
+    // the table calls for the tbl preprocessor in the leading comment
+    assert!(rendered.starts_with(
+        "'\\\" t\n.Dd $Mdocdate$\n.Dt TEST 7\n.Os\n.Sh NAME\n.Nm test\n.Nd Some text with a link\n"
+    ));
+    assert!(rendered.contains(".Sh Usage\n"));
+    assert!(!rendered.contains(".TH"));
+    assert!(!rendered.contains(".SH"));
+}
+
+#[test]
+fn blocks_are_translated() {
+    let rendered = mdoc();
+
+    assert!(rendered.contains(".Lk https://example\\.com\n"));
+    assert!(
+        rendered.contains(".Bd -literal\nfn main() {}\n.Ed\n"),
+        "{}",
+        rendered
+    );
+    assert!(rendered.contains(".Bl -tag -width Ds\n.It \\fB\\-\\-verbose\\fR\nprint more\n"));
+    assert!(rendered.contains(".Pp\nClosing paragraph\\."));
+    // lists end before the blocks following them
+    assert!(rendered.contains("print less\n.El\n.Bd"), "{}", rendered);
+    assert!(rendered.contains("two\n.El\n.TS"), "{}", rendered);
+    assert!(!rendered.contains(".Sh Usage\n.br"));
+    assert!(!rendered.contains(".PD"));
+    assert_eq!(
+        rendered.matches(".Bl").count(),
+        rendered.matches(".El").count()
+    );
+    assert_eq!(
+        rendered.matches(".Bd").count(),
+        rendered.matches(".Ed").count()
+    );
+    for request in [".P\n", ".TP", ".IP", ".RS", ".EX", ".UR"] {
+        assert!(
+            !rendered.contains(request),
+            "{} left in {}",
+            request,
+            rendered
+        );
+    }
+}
+
+#[test]
+#[ignore = "needs mandoc, run with `cargo test -- --ignored`"]
+fn mdoc_passes_mandoc_lint() {
+    let mut child = Command::new("mandoc")
+        .args(["-mdoc", "-Tlint", "-Wwarning"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("mandoc is installed");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(mdoc().as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+}
@@ -0,0 +1,36 @@
+mod common;
+
+use std::{env, fs};
+
+/// Compares `rendered` with the snapshot stored in `tests/fixtures/<name>`. Setting
+/// `UPDATE_SNAPSHOTS=1` overwrites the stored snapshot instead.
+fn assert_snapshot(name: &str, rendered: &str) {
+    let path = common::fixture_path(name);
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::write(&path, rendered).unwrap();
+    }
+    let expected = fs::read_to_string(&path).unwrap();
+
+    assert_eq!(rendered, expected);
+}
+
+#[test]
+fn renders_fixture_book() {
+    let ctx = common::fixture_book("book");
+    let rendered = mdbook_man::mdbook_to_string(&ctx).unwrap();
+
+    assert_snapshot("book.man", &rendered);
+}
+
+#[test]
+fn book_renders_to_bytes_and_writer() {
+    let ctx = common::fixture_book("book");
+    let rendered = mdbook_man::mdbook_to_string(&ctx).unwrap();
+
+    let bytes = mdbook_man::mdbook_to_bytes(&ctx).unwrap();
+    assert_eq!(bytes, rendered.as_bytes());
+
+    let mut written = vec![];
+    mdbook_man::mdbook_to_writer(&ctx, &mut written).unwrap();
+    assert_eq!(written, rendered.as_bytes());
+}
@@ -0,0 +1,84 @@
+mod common;
+
+use common::render;
+
+const SYNOPSIS: &str = "```console\n$ mytool [--verbose] <input> [output]...\n```\n";
+
+#[test]
+fn synopsis_chapter_renders_usage_line() {
+    let rendered = render("", &[("synopsis", SYNOPSIS), ("Usage", "text")]);
+
+    assert!(rendered.contains(".SH SYNOPSIS\n"));
+    assert!(rendered.contains(
+        "\\fBmytool\\fR [\\fB\\-\\-verbose\\fR] \\fIinput\\fR [\\fIoutput\\fR]\\.\\.\\."
+    ));
+    assert!(!rendered.contains(".EX"));
+    assert!(rendered.contains(".SH Usage\n"));
+}
+
+#[test]
+fn synopsis_chapter_name_is_configurable() {
+    let rendered = render(
+        "synopsis-chapter = \"How to run\"",
+        &[
+            ("How To Run", SYNOPSIS),
+            ("Synopsis", "```\nnot usage\n```\n"),
+        ],
+    );
+
+    assert!(rendered.contains(".SH SYNOPSIS\n"));
+    assert!(rendered.contains(".SH Synopsis\n"));
+    assert!(rendered.contains(".EX\nnot usage"));
+}
+
+const INTRO: &str = "\
+# mytool
+
+A tool doing things.
+
+```console
+$ mytool [--verbose] <input>
+```
+
+More text.
+
+```console
+$ mytool --help
+```
+";
+
+#[test]
+fn usage_block_is_promoted_to_synopsis() {
+    let rendered = render(
+        "synopsis-from-usage = true",
+        &[("Intro", INTRO), ("Usage", "text")],
+    );
+
+    assert!(rendered.contains(
+        ".SH SYNOPSIS\n.P\n\\fBmytool\\fR [\\fB\\-\\-verbose\\fR] \\fIinput\\fR\n.SH Intro\n"
+    ));
+    assert_eq!(rendered.matches("\\-\\-verbose").count(), 1);
+    assert!(rendered.contains(".EX\n$ mytool \\-\\-help"));
+}
+
+#[test]
+fn usage_block_is_not_promoted_by_default() {
+    let rendered = render("", &[("Intro", INTRO)]);
+
+    assert!(!rendered.contains("SYNOPSIS"));
+    assert!(rendered.contains(".EX\n$ mytool [\\-\\-verbose] <input>"));
+}
+
+#[test]
+fn usage_block_must_follow_the_introduction() {
+    let intro = "* a list\n\n```console\n$ mytool\n```\n";
+    let rendered = render("synopsis-from-usage = true", &[("Intro", intro)]);
+    assert!(!rendered.contains("SYNOPSIS"));
+
+    let rendered = render(
+        "synopsis-from-usage = true",
+        &[("Intro", INTRO), ("Synopsis", SYNOPSIS)],
+    );
+    assert_eq!(rendered.matches(".SH SYNOPSIS").count(), 1);
+    assert!(rendered.contains(".EX\n$ mytool [\\-\\-verbose] <input>"));
+}
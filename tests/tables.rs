@@ -0,0 +1,85 @@
+mod common;
+
+use common::render;
+
+const RAGGED: &str = "\
+| Name | Size | Note |
+|:-----|-----:|:----:|
+| a | 1 | *x* |
+| .b | 22 |
+| c |
+
+After
+";
+
+#[test]
+fn tables_are_rendered_with_tbl() {
+    let rendered = render("", &[("Table", RAGGED)]);
+
+    assert!(rendered.starts_with("'\\\" t\n.TH test 7 \"\" \"\" \"Miscellaneous\"\n"));
+    assert!(rendered
+        .contains(".TS\ntab(\t);\nlb rb cb\nl r c.\n\\&Name\tSize\tNote\n_\n\\&a\t1\t\\fIx\\fR\n"));
+    assert!(mdbook_man::lint_page(&rendered).is_empty());
+}
+
+#[test]
+fn ragged_rows_are_padded() {
+    let rendered = render("", &[("Table", RAGGED)]);
+
+    assert!(rendered.contains("\n\\&\\.b\t22\t\n\\&c\t\t\n.TE\n.P\nAfter"));
+}
+
+#[test]
+fn empty_header_is_left_out() {
+    let rendered = render("", &[("Table", "| | |\n|---|---|\n| a | b |\n")]);
+
+    assert!(rendered.contains(".TS\ntab(\t);\nl l.\n\\&a\tb\n.TE"));
+    assert!(!rendered.contains("\n_\n"));
+}
+
+#[test]
+fn pages_without_tables_are_not_preprocessed() {
+    let rendered = render("", &[("Text", "no table here\n")]);
+
+    assert!(rendered.starts_with(".TH test 7 \"\" \"\" \"Miscellaneous\"\n"));
+}
+
+#[test]
+fn plain_tables_are_aligned_and_padded() {
+    let rendered = render("table-style = \"plain\"", &[("Table", RAGGED)]);
+
+    assert!(rendered.contains(concat!(
+        ".P\n.nf\n",
+        "Name  Size  Note\n",
+        "\\-\\-\\-\\-\\-\\-\\-\\-\\-\\-\\-\\-\\-\\-\\-\\-\n",
+        "a        1   x\n",
+        "\\.b      22\n",
+        "c\n",
+        ".fi\n",
+    )));
+    assert!(!rendered.contains(".TS"));
+}
+
+#[test]
+fn table_captions_are_centered_in_bold() {
+    let markdown = "Table: Exit codes\n\n| Code | Meaning |\n|---|---|\n| 0 | success |\n\nText.\n\nTable: is not next to a table\n";
+
+    let rendered = render("", &[("Table", markdown)]);
+    assert!(rendered.contains(".P\nTable: Exit codes\n"));
+
+    let rendered = render("table-captions = true", &[("Table", markdown)]);
+    assert!(
+        rendered.contains(".ce 1\n\\fBExit codes\\fR\n.TS\n"),
+        "{}",
+        rendered
+    );
+    assert!(rendered.contains(".P\nTable: is not next to a table"));
+
+    let below = "| Code |\n|---|\n| 0 |\n\nTable: Exit codes\n";
+    let rendered = render("table-captions = true", &[("Table", below)]);
+    assert!(
+        rendered.contains(".TE\n.ce 1\n\\fBExit codes\\fR"),
+        "{}",
+        rendered
+    );
+}
@@ -0,0 +1,169 @@
+mod common;
+
+use common::render;
+
+const CHAPTER: &str = "\
+Before <span>Ctrl</span> after
+
+<div class=\"note\">
+hidden
+</div>
+
+***
+
+End
+";
+
+#[test]
+fn unhandled_nodes_are_dropped_by_default() {
+    let rendered = render("", &[("Html", CHAPTER)]);
+
+    assert!(rendered.contains(".P\nBefore Ctrl after\n"));
+    assert!(!rendered.contains("hidden"));
+    assert!(!rendered.contains("unhandled"));
+}
+
+#[test]
+fn unhandled_nodes_as_text() {
+    let rendered = render("unhandled-nodes = \"text\"", &[("Html", CHAPTER)]);
+
+    assert!(rendered.contains(".P\nBefore <span>Ctrl</span> after\n"));
+    assert!(rendered.contains(".P\n<div class=\"note\">\nhidden\n</div>\n"));
+}
+
+#[test]
+fn unhandled_nodes_as_comments() {
+    let rendered = render("unhandled-nodes = \"comment\"", &[("Html", CHAPTER)]);
+
+    assert!(rendered.contains(
+        "Before \n.\\\" unhandled inline html\nCtrl\n.\\\" unhandled inline html\nafter"
+    ));
+    assert!(rendered.contains(".\\\" unhandled html block\n"));
+    assert!(rendered.contains(".\\\" unhandled thematic break\n"));
+    assert!(!rendered.contains("hidden"));
+}
+
+#[test]
+fn unsafe_html_is_kept_in_the_page() {
+    let chapter = format!("<!-- kept as a comment -->\n\n{}", CHAPTER);
+    let rendered = render("unsafe-html = true", &[("Html", &chapter)]);
+
+    assert!(
+        rendered.contains(".\\\" kept as a comment\n"),
+        "{}",
+        rendered
+    );
+    assert!(rendered.contains("<div class=\"note\">\nhidden\n</div>"));
+    assert!(rendered.contains("Before <span>Ctrl</span> after"));
+
+    let rendered = render("", &[("Html", &chapter)]);
+    assert!(!rendered.contains("kept as a comment"));
+    assert!(!rendered.contains("div"));
+}
+
+#[test]
+fn details_show_their_summary_above_the_content() {
+    let chapter = "\
+<details>
+<summary>More <b>options</b></summary>
+
+Hidden *content*
+
+</details>
+
+<details><summary>Short</summary>inline text</details>
+";
+    let rendered = render("", &[("Html", chapter)]);
+
+    assert!(
+        rendered.contains(".P\n\\fBMore options\\fR\n.RS\n.P\nHidden \\fIcontent\\fR\n.RE\n"),
+        "{}",
+        rendered
+    );
+    assert!(rendered.contains(".P\n\\fBShort\\fR\n.RS\n.P\ninline text\n.RE\n"));
+    assert!(!rendered.contains("details"));
+}
+
+#[test]
+fn kbd_keys_are_styled() {
+    let chapter = "Press <kbd>Ctrl</kbd>+<kbd>C</kbd> to stop.\n";
+
+    let rendered = render("", &[("Keys", chapter)]);
+    assert!(rendered.contains("Press \\fBCtrl\\fR+\\fBC\\fR to stop"));
+
+    let rendered = render("kbd-style = \"brackets\"", &[("Keys", chapter)]);
+    assert!(rendered.contains("Press [Ctrl]+[C] to stop"));
+}
+
+#[test]
+fn strict_mode_fails_on_unhandled_nodes() {
+    let ctx = common::book("test", "strict = true", &[("Html", CHAPTER)]);
+
+    let err = mdbook_man::mdbook_to_string(&ctx).unwrap_err();
+    assert!(matches!(
+        &err,
+        mdbook_man::Error::Unhandled { chapter, element, line: 1 }
+            if chapter == "Html" && element == "inline html"
+    ));
+    assert_eq!(
+        err.to_string(),
+        "chapter `Html` contains an unsupported inline html on line 1"
+    );
+
+    let ctx = common::book(
+        "test",
+        "strict = true\nsplit-chapters = true",
+        &[("Html", CHAPTER)],
+    );
+    assert!(mdbook_man::mdbook_to_roff_chapters(&ctx).is_err());
+
+    let ctx = common::book("test", "strict = true", &[("Plain", "*only* `text`\n")]);
+    assert!(mdbook_man::mdbook_to_string(&ctx).is_ok());
+    assert!(render("", &[("Html", CHAPTER)]).contains("End"));
+}
+
+#[test]
+fn samp_var_and_br_are_rendered_by_their_meaning() {
+    let chapter = "Set <var>name</var> to see <samp>name: ok</samp>.<br>Then <span>stop</span>.\n";
+
+    let rendered = render("", &[("Html", chapter)]);
+    assert!(
+        rendered.contains("Set \\fIname\\fR to see \\f(CRname: ok\\fR\\.\n.br\nThen stop\\."),
+        "{}",
+        rendered
+    );
+    assert!(!rendered.contains("var"));
+    assert!(!rendered.contains("span"));
+}
+
+#[test]
+fn samp_combines_with_enclosing_styles() {
+    let rendered = render(
+        "",
+        &[("Html", "**bold <samp>out</samp>** and *<samp>it</samp>*\n")],
+    );
+
+    assert!(
+        rendered.contains("\\fBbold\\fR \\f(CBout\\fR and \\f(CIit\\fR"),
+        "{}",
+        rendered
+    );
+}
+
+#[test]
+fn unclosed_elements_end_with_their_block() {
+    let chapter = "Press <kbd>Ctrl\n\nSet <var>name and <samp>out\n\n## Next\n\nPlain text.\n";
+
+    let rendered = render("", &[("Html", chapter)]);
+    assert!(rendered.contains("Press \\fBCtrl\\fR\n"), "{}", rendered);
+    assert!(
+        rendered.contains("Set \\fIname and\\fR \\f(CIout\\fR\n"),
+        "{}",
+        rendered
+    );
+    assert!(rendered.ends_with(".P\nPlain text\\."), "{}", rendered);
+    assert!(rendered.contains("\\fBNext\\fR\n"), "{}", rendered);
+
+    let rendered = render("kbd-style = \"brackets\"", &[("Html", chapter)]);
+    assert!(rendered.contains("Press [Ctrl]\n"), "{}", rendered);
+}